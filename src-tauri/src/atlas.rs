@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub(crate) struct AtlasRegion {
+    pub(crate) name: String,
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    #[serde(rename = "origWidth")]
+    pub(crate) orig_width: u32,
+    #[serde(rename = "origHeight")]
+    pub(crate) orig_height: u32,
+    #[serde(rename = "offsetX")]
+    pub(crate) offset_x: i32,
+    #[serde(rename = "offsetY")]
+    pub(crate) offset_y: i32,
+    pub(crate) degrees: u32,
+    pub(crate) index: i32,
+    pub(crate) split: Option<[i32; 4]>,
+    pub(crate) pad: Option<[i32; 4]>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub(crate) struct AtlasPage {
+    pub(crate) name: String,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) format: Option<String>,
+    #[serde(rename = "minFilter")]
+    pub(crate) min_filter: Option<String>,
+    #[serde(rename = "magFilter")]
+    pub(crate) mag_filter: Option<String>,
+    pub(crate) repeat: Option<String>,
+    pub(crate) pma: bool,
+    pub(crate) scale: Option<f32>,
+    #[serde(rename = "regionCount")]
+    pub(crate) region_count: usize,
+    #[serde(skip)]
+    pub(crate) regions: Vec<AtlasRegion>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Atlas {
+    pub(crate) pages: Vec<AtlasPage>,
+}
+
+fn read_entry(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    let colon = line.find(':')?;
+    let key = line[..colon].trim().to_lowercase();
+    let values = line[colon + 1..]
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .collect();
+    Some((key, values))
+}
+
+fn parse_u32(values: &[String], idx: usize) -> u32 {
+    values
+        .get(idx)
+        .and_then(|v| v.parse::<f32>().ok())
+        .map(|v| v.max(0.0) as u32)
+        .unwrap_or(0)
+}
+
+fn parse_i32(values: &[String], idx: usize) -> i32 {
+    values
+        .get(idx)
+        .and_then(|v| v.parse::<f32>().ok())
+        .map(|v| v as i32)
+        .unwrap_or(0)
+}
+
+fn parse_edges(values: &[String]) -> Option<[i32; 4]> {
+    (values.len() >= 4).then(|| std::array::from_fn(|idx| parse_i32(values, idx)))
+}
+
+fn parse_degrees(value: &str) -> u32 {
+    match value.to_lowercase().as_str() {
+        "true" => 90,
+        "false" => 0,
+        v => v.parse::<i32>().map(|d| d.rem_euclid(360) as u32).unwrap_or(0),
+    }
+}
+
+impl Atlas {
+    pub(crate) fn parse(content: &str) -> Result<Atlas, String> {
+        let mut pages: Vec<AtlasPage> = Vec::new();
+        let mut lines = content
+            .strip_prefix('\u{FEFF}')
+            .unwrap_or(content)
+            .lines()
+            .peekable();
+        let mut in_page = false;
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() {
+                in_page = false;
+                continue;
+            }
+            if !in_page {
+                let mut page = AtlasPage {
+                    name: line.trim().to_string(),
+                    ..Default::default()
+                };
+                while let Some(next) = lines.peek() {
+                    let Some((key, values)) = read_entry(next) else {
+                        break;
+                    };
+                    lines.next();
+                    match key.as_str() {
+                        "size" => {
+                            page.width = parse_u32(&values, 0);
+                            page.height = parse_u32(&values, 1);
+                        }
+                        "format" => page.format = values.first().cloned(),
+                        "filter" => {
+                            page.min_filter = values.first().cloned();
+                            page.mag_filter = values.get(1).or(values.first()).cloned();
+                        }
+                        "repeat" => page.repeat = values.first().cloned(),
+                        "pma" => page.pma = values.first().map(|v| v == "true").unwrap_or(false),
+                        "scale" => page.scale = values.first().and_then(|v| v.parse().ok()),
+                        _ => {}
+                    }
+                }
+                pages.push(page);
+                in_page = true;
+                continue;
+            }
+            let page = pages.last_mut().ok_or("Atlas region found before any page")?;
+            let mut region = AtlasRegion {
+                name: line.trim().to_string(),
+                index: -1,
+                ..Default::default()
+            };
+            let mut has_orig = false;
+            while let Some(next) = lines.peek() {
+                let Some((key, values)) = read_entry(next) else {
+                    break;
+                };
+                lines.next();
+                match key.as_str() {
+                    "xy" => {
+                        region.x = parse_u32(&values, 0);
+                        region.y = parse_u32(&values, 1);
+                    }
+                    "size" => {
+                        region.width = parse_u32(&values, 0);
+                        region.height = parse_u32(&values, 1);
+                    }
+                    "bounds" => {
+                        region.x = parse_u32(&values, 0);
+                        region.y = parse_u32(&values, 1);
+                        region.width = parse_u32(&values, 2);
+                        region.height = parse_u32(&values, 3);
+                    }
+                    "orig" => {
+                        region.orig_width = parse_u32(&values, 0);
+                        region.orig_height = parse_u32(&values, 1);
+                        has_orig = true;
+                    }
+                    "offset" => {
+                        region.offset_x = parse_i32(&values, 0);
+                        region.offset_y = parse_i32(&values, 1);
+                    }
+                    "offsets" => {
+                        region.offset_x = parse_i32(&values, 0);
+                        region.offset_y = parse_i32(&values, 1);
+                        region.orig_width = parse_u32(&values, 2);
+                        region.orig_height = parse_u32(&values, 3);
+                        has_orig = true;
+                    }
+                    "rotate" => {
+                        region.degrees = values.first().map(|v| parse_degrees(v)).unwrap_or(0);
+                    }
+                    "index" => region.index = parse_i32(&values, 0),
+                    "split" => region.split = parse_edges(&values),
+                    "pad" => region.pad = parse_edges(&values),
+                    _ => {}
+                }
+            }
+            if !has_orig {
+                region.orig_width = region.width;
+                region.orig_height = region.height;
+            }
+            page.regions.push(region);
+        }
+        if pages.is_empty() {
+            return Err("Atlas contains no pages".to_string());
+        }
+        for page in &mut pages {
+            page.region_count = page.regions.len();
+        }
+        Ok(Atlas { pages })
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        let mut out = String::new();
+        for page in &self.pages {
+            out.push('\n');
+            out.push_str(&format!("{}\n", page.name));
+            out.push_str(&format!("size: {},{}\n", page.width, page.height));
+            out.push_str(&format!("format: {}\n", page.format.as_deref().unwrap_or("RGBA8888")));
+            out.push_str(&format!(
+                "filter: {},{}\n",
+                page.min_filter.as_deref().unwrap_or("Linear"),
+                page.mag_filter.as_deref().unwrap_or("Linear")
+            ));
+            out.push_str(&format!("repeat: {}\n", page.repeat.as_deref().unwrap_or("none")));
+            if page.pma {
+                out.push_str("pma: true\n");
+            }
+            if let Some(scale) = page.scale {
+                out.push_str(&format!("scale: {}\n", scale));
+            }
+            for region in &page.regions {
+                let rotate = match region.degrees {
+                    0 => "false".to_string(),
+                    90 => "true".to_string(),
+                    degrees => degrees.to_string(),
+                };
+                out.push_str(&format!("{}\n", region.name));
+                out.push_str(&format!("  rotate: {}\n", rotate));
+                out.push_str(&format!("  xy: {}, {}\n", region.x, region.y));
+                out.push_str(&format!("  size: {}, {}\n", region.width, region.height));
+                if let Some([left, right, top, bottom]) = region.split {
+                    out.push_str(&format!("  split: {}, {}, {}, {}\n", left, right, top, bottom));
+                }
+                if let Some([left, right, top, bottom]) = region.pad {
+                    out.push_str(&format!("  pad: {}, {}, {}, {}\n", left, right, top, bottom));
+                }
+                out.push_str(&format!("  orig: {}, {}\n", region.orig_width, region.orig_height));
+                out.push_str(&format!("  offset: {}, {}\n", region.offset_x, region.offset_y));
+                out.push_str(&format!("  index: {}\n", region.index));
+            }
+        }
+        out
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<Atlas, String> {
+        let content = fs::read(path).map_err(|e| format!("Failed to read atlas: {}", e))?;
+        Atlas::parse(&String::from_utf8_lossy(&content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATLAS: &str = "
+hero.png
+size: 64, 32
+format: RGBA8888
+filter: Linear, Nearest
+repeat: none
+pma: true
+button
+  bounds: 2, 4, 10, 12
+  offsets: 1, 2, 14, 16
+  rotate: 90
+  split: 1, 2, 3, 4
+  pad: 5, 6, 7, 8
+head
+  xy: 20, 0
+  size: 8, 8
+  index: 3
+";
+
+    #[test]
+    fn parses_split_and_pad() {
+        let atlas = Atlas::parse(ATLAS).unwrap();
+        let page = &atlas.pages[0];
+        assert_eq!((page.width, page.height), (64, 32));
+        assert!(page.pma);
+        let button = &page.regions[0];
+        assert_eq!((button.x, button.y, button.width, button.height), (2, 4, 10, 12));
+        assert_eq!((button.orig_width, button.orig_height), (14, 16));
+        assert_eq!((button.offset_x, button.offset_y), (1, 2));
+        assert_eq!(button.degrees, 90);
+        assert_eq!(button.split, Some([1, 2, 3, 4]));
+        assert_eq!(button.pad, Some([5, 6, 7, 8]));
+        let head = &page.regions[1];
+        assert_eq!((head.orig_width, head.orig_height), (8, 8));
+        assert_eq!(head.split, None);
+        assert_eq!(head.index, 3);
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let atlas = Atlas::parse(ATLAS).unwrap();
+        let text = atlas.to_text();
+        let reparsed = Atlas::parse(&text).unwrap();
+        assert_eq!(reparsed.to_text(), text);
+        assert_eq!(reparsed.pages.len(), 1);
+        let (page, original) = (&reparsed.pages[0], &atlas.pages[0]);
+        assert_eq!(page.min_filter.as_deref(), Some("Linear"));
+        assert_eq!(page.mag_filter.as_deref(), Some("Nearest"));
+        assert_eq!(page.region_count, 2);
+        for (region, expected) in page.regions.iter().zip(&original.regions) {
+            assert_eq!(region.name, expected.name);
+            assert_eq!(
+                (region.x, region.y, region.width, region.height),
+                (expected.x, expected.y, expected.width, expected.height)
+            );
+            assert_eq!(
+                (region.orig_width, region.orig_height),
+                (expected.orig_width, expected.orig_height)
+            );
+            assert_eq!(
+                (region.offset_x, region.offset_y),
+                (expected.offset_x, expected.offset_y)
+            );
+            assert_eq!(region.degrees, expected.degrees);
+            assert_eq!(region.index, expected.index);
+            assert_eq!(region.split, expected.split);
+            assert_eq!(region.pad, expected.pad);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use super::{DetectContext, Detector};
+use crate::live2d;
+use crate::scene::{self, SceneData, SceneKind, Severity};
+
+pub(crate) struct Cubism2Detector;
+
+impl Detector for Cubism2Detector {
+    fn detect(&self, ctx: &mut DetectContext) -> Vec<SceneData> {
+        let dir = ctx.dir();
+        let moc_files: Vec<_> = ctx
+            .unclaimed()
+            .filter(|f| f.lower.contains(".moc") && !f.lower.contains(".moc3") && !f.lower.contains(".atlas"))
+            .collect();
+        let mut scenes = Vec::new();
+        for file in moc_files {
+            let Some(moc_pos) = file.lower.find(".moc") else {
+                continue;
+            };
+            ctx.claim(&file.name);
+            let moc_stem = &file.name[..moc_pos];
+            let write_error = match scene::cubism2_model_json_name(dir, moc_stem) {
+                Some(model_file) => {
+                    ctx.claim(&model_file);
+                    None
+                }
+                None if ctx.options.read_only => None,
+                None => live2d::write_cubism2_model_json(dir, &file.name, moc_stem, ctx.options.fix_in_place).err(),
+            };
+            let scene_path = file.scene_path();
+            let extension_part = &file.name[moc_pos..];
+            let mut scene = SceneData::new(
+                SceneKind::Cubism2,
+                scene_path[..scene_path.len() - extension_part.len()].to_string(),
+                extension_part.to_string(),
+                "".to_string(),
+            );
+            if let Some(e) = write_error {
+                let model_file = format!("{}.json", moc_stem);
+                scene.add_diagnostic(
+                    Severity::Error,
+                    "writeFailed",
+                    format!("Failed to write a generated {}: {}", model_file, e),
+                    Some(scene.scene_path(&model_file)),
+                );
+            }
+            scene::resolve_cubism2(dir, &file.name, moc_stem, &mut scene);
+            scenes.push(scene);
+        }
+        scenes
+    }
+}
@@ -0,0 +1,50 @@
+use super::{DetectContext, Detector};
+use crate::live2d;
+use crate::scene::{self, SceneData, SceneKind, Severity};
+
+pub(crate) struct Cubism3Detector;
+
+impl Detector for Cubism3Detector {
+    fn detect(&self, ctx: &mut DetectContext) -> Vec<SceneData> {
+        let dir = ctx.dir();
+        let moc3_files: Vec<_> = ctx
+            .unclaimed()
+            .filter(|f| f.lower.contains(".moc3") && !f.lower.contains(".atlas"))
+            .collect();
+        let dir_files = ctx.listing.names();
+        let mut scenes = Vec::new();
+        for file in moc3_files {
+            let Some(moc3_pos) = file.lower.find(".moc3") else {
+                continue;
+            };
+            ctx.claim(&file.name);
+            let moc_stem = &file.name[..moc3_pos];
+            let model_file = format!("{}.model3.json", moc_stem);
+            ctx.claim(&model_file);
+            let write_error = if ctx.options.read_only || dir.join(&model_file).exists() {
+                None
+            } else {
+                live2d::write_model3_json(dir, &file.name, moc_stem, &dir_files, ctx.options.fix_in_place).err()
+            };
+            let scene_path = file.scene_path();
+            let extension_part = &file.name[moc3_pos..];
+            let mut scene = SceneData::new(
+                SceneKind::Cubism3,
+                scene_path[..scene_path.len() - extension_part.len()].to_string(),
+                extension_part.to_string(),
+                "".to_string(),
+            );
+            if let Some(e) = write_error {
+                scene.add_diagnostic(
+                    Severity::Error,
+                    "writeFailed",
+                    format!("Failed to write a generated {}: {}", model_file, e),
+                    Some(scene.scene_path(&model_file)),
+                );
+            }
+            scene::resolve_cubism3(dir, &file.name, moc_stem, ctx.options.read_only, &mut scene);
+            scenes.push(scene);
+        }
+        scenes
+    }
+}
@@ -0,0 +1,31 @@
+use super::{DetectContext, Detector};
+use crate::scene::{self, SceneData, SceneKind};
+
+pub(crate) struct LayeredSpriteDetector;
+
+impl Detector for LayeredSpriteDetector {
+    fn detect(&self, ctx: &mut DetectContext) -> Vec<SceneData> {
+        let meta_files: Vec<_> = ctx
+            .unclaimed()
+            .filter(|f| f.lower == "meta.json" || f.lower.ends_with(".meta.json"))
+            .collect();
+        let mut scenes = Vec::new();
+        for file in meta_files {
+            ctx.claim(&file.name);
+            let (name, extension) = if file.lower == "meta.json" {
+                ("meta", ".json")
+            } else {
+                (&file.name[..file.name.len() - ".meta.json".len()], ".meta.json")
+            };
+            let mut scene = SceneData::new(
+                SceneKind::LayeredSprite,
+                name.to_string(),
+                extension.to_string(),
+                "".to_string(),
+            );
+            scene::resolve_layered_sprite(ctx.dir(), &file.name, &mut scene);
+            scenes.push(scene);
+        }
+        scenes
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::downscale;
+use crate::scene::{CompanionRule, Diagnostic, ScanOptions, SceneData, Severity, default_companion_rules};
+
+mod cubism2;
+mod cubism3;
+mod layered_sprite;
+mod spine;
+
+pub(crate) use cubism2::Cubism2Detector;
+pub(crate) use cubism3::Cubism3Detector;
+pub(crate) use layered_sprite::LayeredSpriteDetector;
+pub(crate) use spine::{SpineDetector, pack_loose_images};
+
+pub(crate) const FOLDER_RULES_FILE: &str = "spive2d.rules.json";
+
+static BUILTIN_DETECTORS: LazyLock<DetectorRegistry> = LazyLock::new(DetectorRegistry::builtin);
+
+#[derive(Clone, Debug)]
+pub(crate) struct DirFile {
+    pub(crate) name: String,
+    pub(crate) lower: String,
+    pub(crate) rel_path: String,
+}
+
+impl DirFile {
+    pub(crate) fn scene_path(&self) -> &str {
+        match self.rel_path.find('/') {
+            Some(idx) => &self.rel_path[idx + 1..],
+            None => &self.rel_path,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct DirListing {
+    pub(crate) dir: PathBuf,
+    pub(crate) files: Vec<DirFile>,
+}
+
+impl DirListing {
+    pub(crate) fn read(dir: &Path, base_path: &Path) -> Result<Self, String> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let entry_path = entry.path();
+            if !entry_path.is_file() {
+                continue;
+            }
+            let Some(name) = entry_path.file_name().and_then(|f| f.to_str()) else {
+                continue;
+            };
+            let rel_path = entry_path
+                .strip_prefix(base_path)
+                .map(|p| p.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+                .unwrap_or_else(|_| name.to_string());
+            files.push(DirFile {
+                name: name.to_string(),
+                lower: name.to_lowercase(),
+                rel_path,
+            });
+        }
+        files.sort_unstable_by(|a, b| crate::compare_natural(&a.name, &b.name));
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            files,
+        })
+    }
+
+    pub(crate) fn names(&self) -> Vec<String> {
+        self.files.iter().map(|f| f.name.clone()).collect()
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct FolderRules {
+    companions: Option<Vec<CompanionRule>>,
+}
+
+pub(crate) struct DetectContext<'a> {
+    pub(crate) listing: &'a DirListing,
+    pub(crate) options: &'a ScanOptions,
+    pub(crate) companion_rules: Vec<CompanionRule>,
+    diagnostics: Vec<Diagnostic>,
+    claimed: HashSet<String>,
+}
+
+impl<'a> DetectContext<'a> {
+    pub(crate) fn new(listing: &'a DirListing, options: &'a ScanOptions) -> Self {
+        let mut ctx = Self {
+            listing,
+            options,
+            companion_rules: options.companions.clone().unwrap_or_else(default_companion_rules),
+            diagnostics: Vec::new(),
+            claimed: HashSet::new(),
+        };
+        ctx.read_folder_rules();
+        ctx
+    }
+
+    fn read_folder_rules(&mut self) {
+        let Some(file) = self.listing.files.iter().find(|f| f.lower == FOLDER_RULES_FILE) else {
+            return;
+        };
+        self.claim(&file.name);
+        let rules = fs::read(self.dir().join(&file.name))
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&data);
+                serde_json::from_slice::<FolderRules>(data).map_err(|e| e.to_string())
+            });
+        match rules {
+            Ok(rules) => {
+                if let Some(companions) = rules.companions {
+                    self.companion_rules = companions;
+                }
+            }
+            Err(e) => self.add_diagnostic(
+                Severity::Warning,
+                "invalidRules",
+                format!("Failed to read {}: {}", file.name, e),
+                Some(file.rel_path.clone()),
+            ),
+        }
+    }
+
+    pub(crate) fn add_diagnostic(&mut self, severity: Severity, code: &str, message: String, file: Option<String>) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            code: code.to_string(),
+            message,
+            file,
+        });
+    }
+
+    pub(crate) fn dir(&self) -> &'a Path {
+        &self.listing.dir
+    }
+
+    pub(crate) fn unclaimed(&self) -> impl Iterator<Item = &'a DirFile> + '_ {
+        self.listing
+            .files
+            .iter()
+            .filter(|f| !self.claimed.contains(&f.lower))
+    }
+
+    pub(crate) fn claim(&mut self, file_name: &str) {
+        self.claimed.insert(file_name.to_lowercase());
+    }
+}
+
+pub(crate) trait Detector: Send + Sync {
+    fn detect(&self, ctx: &mut DetectContext) -> Vec<SceneData>;
+}
+
+pub(crate) struct DetectorRegistry {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl DetectorRegistry {
+    pub(crate) fn new() -> Self {
+        Self { detectors: Vec::new() }
+    }
+
+    pub(crate) fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Cubism3Detector);
+        registry.register(Cubism2Detector);
+        registry.register(LayeredSpriteDetector);
+        registry.register(SpineDetector);
+        registry
+    }
+
+    pub(crate) fn register(&mut self, detector: impl Detector + 'static) {
+        self.detectors.push(Box::new(detector));
+    }
+
+    pub(crate) fn detect(&self, listing: &DirListing, options: &ScanOptions) -> Vec<SceneData> {
+        let mut ctx = DetectContext::new(listing, options);
+        let mut scenes: Vec<SceneData> = self
+            .detectors
+            .iter()
+            .flat_map(|detector| detector.detect(&mut ctx))
+            .collect();
+        if let Some(max_size) = options.max_texture_size.filter(|size| *size > 0 && !options.read_only) {
+            for scene in &mut scenes {
+                downscale::limit_scene(&listing.dir, scene, max_size);
+            }
+        }
+        if let Some(scene) = scenes.first_mut() {
+            scene.diagnostics.extend(ctx.diagnostics);
+        }
+        scenes
+    }
+}
+
+pub(crate) fn detectors() -> &'static DetectorRegistry {
+    &BUILTIN_DETECTORS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::SceneKind;
+
+    const SKELETON: &str = r#"{
+        "skeleton": { "spine": "4.1.20" },
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "body", "bone": "root", "attachment": "body" }],
+        "skins": [{ "name": "default", "attachments": { "body": { "body": { "width": 8, "height": 8 } } } }]
+    }"#;
+
+    const ATLAS: &str = "\nhero.png\nsize: 8, 8\nfilter: Linear, Linear\nbody\n  bounds: 0, 0, 8, 8\n";
+
+    struct ClaimAll;
+
+    impl Detector for ClaimAll {
+        fn detect(&self, ctx: &mut DetectContext) -> Vec<SceneData> {
+            let names: Vec<String> = ctx.unclaimed().map(|f| f.name.clone()).collect();
+            for name in names {
+                ctx.claim(&name);
+            }
+            Vec::new()
+        }
+    }
+
+    fn write_png(dir: &Path, name: &str) {
+        image::RgbaImage::new(8, 8).save(dir.join(name)).unwrap();
+    }
+
+    fn write_spine(dir: &Path) {
+        fs::write(dir.join("hero.json"), SKELETON).unwrap();
+        fs::write(dir.join("hero.atlas"), ATLAS).unwrap();
+        write_png(dir, "hero.png");
+    }
+
+    fn detect_with(registry: &DetectorRegistry, dir: &Path) -> Vec<SceneData> {
+        let listing = DirListing::read(dir, dir).unwrap();
+        registry.detect(&listing, &ScanOptions::default())
+    }
+
+    fn has_diagnostic(scene: &SceneData, code: &str) -> bool {
+        scene.diagnostics.iter().any(|d| d.code == code)
+    }
+
+    #[test]
+    fn detects_spine_skeleton_with_atlas() {
+        let dir = tempfile::tempdir().unwrap();
+        write_spine(dir.path());
+        let scenes = detect_with(detectors(), dir.path());
+        assert_eq!(scenes.len(), 1);
+        let scene = &scenes[0];
+        assert_eq!(scene.kind, SceneKind::Spine);
+        assert_eq!(scene.name, "hero");
+        assert_eq!(scene.main_ext, ".json");
+        assert_eq!(scene.atlas_ext, ".atlas");
+        assert_eq!(scene.atlas_pages.len(), 1);
+        assert!(scene.diagnostics.iter().all(|d| d.severity != Severity::Error));
+    }
+
+    #[test]
+    fn generates_model3_json_for_lone_moc3() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hero.moc3"), b"MOC3\x03\0\0\0").unwrap();
+        write_png(dir.path(), "hero.png");
+        let scenes = detect_with(detectors(), dir.path());
+        assert_eq!(scenes.len(), 1);
+        let scene = &scenes[0];
+        assert_eq!(scene.kind, SceneKind::Cubism3);
+        assert_eq!(scene.name, "hero");
+        assert!(scene.version.is_some());
+        assert!(scene.overlay.contains_key("hero.model3.json"));
+        assert!(has_diagnostic(scene, "generatedModelJson"));
+        assert!(!dir.path().join("hero.model3.json").exists());
+    }
+
+    #[test]
+    fn skips_model3_json_generation_when_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hero.moc3"), b"MOC3\x03\0\0\0").unwrap();
+        let listing = DirListing::read(dir.path(), dir.path()).unwrap();
+        let options = ScanOptions {
+            read_only: true,
+            ..Default::default()
+        };
+        let scenes = detectors().detect(&listing, &options);
+        assert_eq!(scenes.len(), 1);
+        assert!(scenes[0].overlay.is_empty());
+        assert!(has_diagnostic(&scenes[0], "missingModelJson"));
+    }
+
+    #[test]
+    fn generates_model_json_for_cubism2_moc() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hero.moc"), b"moc\n\0\0\0\0").unwrap();
+        write_png(dir.path(), "hero.png");
+        let scenes = detect_with(detectors(), dir.path());
+        assert_eq!(scenes.len(), 1);
+        let scene = &scenes[0];
+        assert_eq!(scene.kind, SceneKind::Cubism2);
+        assert_eq!(scene.version.as_deref(), Some("2.1"));
+        assert!(scene.overlay.contains_key("hero.json"));
+        assert!(has_diagnostic(scene, "generatedModelJson"));
+    }
+
+    #[test]
+    fn detects_layered_sprite() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("meta.json"), r#"{ "atlas": "body.png" }"#).unwrap();
+        write_png(dir.path(), "body.png");
+        let scenes = detect_with(detectors(), dir.path());
+        assert_eq!(scenes.len(), 1);
+        let scene = &scenes[0];
+        assert_eq!(scene.kind, SceneKind::LayeredSprite);
+        assert_eq!(scene.name, "meta");
+        assert!(scene.assets.iter().any(|asset| asset == "body.png"));
+    }
+
+    #[test]
+    fn earlier_detectors_claim_files_first() {
+        let dir = tempfile::tempdir().unwrap();
+        write_spine(dir.path());
+        let mut registry = DetectorRegistry::new();
+        registry.register(ClaimAll);
+        registry.register(SpineDetector);
+        assert!(detect_with(&registry, dir.path()).is_empty());
+
+        let mut registry = DetectorRegistry::new();
+        registry.register(SpineDetector);
+        registry.register(ClaimAll);
+        assert_eq!(detect_with(&registry, dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn detectors_share_a_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        write_spine(dir.path());
+        fs::write(dir.path().join("girl.moc3"), b"MOC3\x03\0\0\0").unwrap();
+        fs::write(dir.path().join("meta.json"), r#"{ "atlas": "hero.png" }"#).unwrap();
+        let scenes = detect_with(detectors(), dir.path());
+        let kinds: Vec<SceneKind> = scenes.iter().map(|scene| scene.kind).collect();
+        assert_eq!(kinds, [SceneKind::Cubism3, SceneKind::LayeredSprite, SceneKind::Spine]);
+    }
+}
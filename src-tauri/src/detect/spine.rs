@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{DetectContext, Detector, DirFile};
+use crate::atlas::Atlas;
+use crate::overlay;
+use crate::packer::{self, PackOptions};
+use crate::scene::{
+    self, Companion, CompanionRole, CompanionRule, IMAGE_EXTENSIONS, SceneData, SceneKind, Severity, is_image, modified,
+};
+use crate::spine::{self, data::SkeletonData};
+
+const SKELETON_EXTENSIONS: &[&str] = &[".skel", ".json", ".asset"];
+
+pub(crate) struct SpineDetector;
+
+fn named_atlases(files: &[&DirFile], rules: &[CompanionRule]) -> BTreeMap<String, String> {
+    let all_atlases: BTreeMap<String, String> = files
+        .iter()
+        .filter(|f| !is_image(&f.lower))
+        .filter_map(|f| {
+            let idx = f.lower.rfind(".atlas")?;
+            Some((f.name[..idx].to_string(), f.name[idx..].to_string()))
+        })
+        .collect();
+    let suffixes: Vec<String> = rules
+        .iter()
+        .map(|r| r.suffix.to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let main_bases: HashSet<String> = all_atlases
+        .keys()
+        .map(|b| b.to_lowercase())
+        .filter(|b| !suffixes.iter().any(|s| b.contains(s.as_str())))
+        .collect();
+    all_atlases
+        .into_iter()
+        .filter(|(base, _)| {
+            let base_lower = base.to_lowercase();
+            !suffixes
+                .iter()
+                .filter_map(|s| base_lower.rfind(s.as_str()))
+                .any(|pos| main_bases.contains(&base_lower[..pos]))
+        })
+        .collect()
+}
+
+struct SkeletonCandidate<'a> {
+    file: &'a DirFile,
+    main_ext: String,
+    rank: (u8, usize),
+}
+
+fn skeleton_candidates<'a>(files: &[&'a DirFile], base_lower: &str) -> Vec<SkeletonCandidate<'a>> {
+    let mut candidates = Vec::new();
+    for file in files {
+        let candidate = SKELETON_EXTENSIONS.iter().enumerate().find_map(|(ext_index, ext)| {
+            let pattern = format!("{}{}", base_lower, ext);
+            let pos = file.lower.find(&pattern)?;
+            let main_ext = file.name.get(pos + base_lower.len()..)?.to_string();
+            let tier = if file.lower == pattern {
+                0
+            } else if pos == 0 {
+                1
+            } else {
+                2
+            };
+            Some(SkeletonCandidate {
+                file,
+                main_ext,
+                rank: (tier, ext_index),
+            })
+        });
+        candidates.extend(candidate);
+    }
+    candidates.sort_by_key(|c| c.rank);
+    candidates
+}
+
+fn find_skeleton<'a>(dir: &Path, files: &[&'a DirFile], base_lower: &str) -> Vec<SkeletonCandidate<'a>> {
+    let mut candidates: Vec<SkeletonCandidate> = skeleton_candidates(files, base_lower)
+        .into_iter()
+        .filter(|c| spine::is_skeleton_file(&dir.join(&c.file.name)))
+        .collect();
+    if let Some(tier) = candidates.first().map(|c| c.rank.0) {
+        candidates.retain(|c| c.rank.0 == tier);
+    }
+    candidates
+}
+
+fn find_companions(files: &[&DirFile], base_lower: &str, main_ext: &str, rules: &[CompanionRule]) -> Vec<Companion> {
+    let main_ext_lower = main_ext.to_lowercase();
+    let mut companions: Vec<(usize, Companion)> = Vec::new();
+    for file in files {
+        let Some(rest) = file
+            .lower
+            .strip_prefix(base_lower)
+            .and_then(|rest| rest.strip_suffix(main_ext_lower.as_str()))
+        else {
+            continue;
+        };
+        let rule = rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| !rule.suffix.is_empty() && rest.starts_with(&rule.suffix.to_lowercase()))
+            .max_by_key(|(i, rule)| (rule.suffix.len(), std::cmp::Reverse(*i)));
+        if let Some((index, rule)) = rule
+            && let Some(file) = file.name.get(base_lower.len()..)
+        {
+            companions.push((
+                index,
+                Companion {
+                    file: file.to_string(),
+                    role: rule.role,
+                },
+            ));
+        }
+    }
+    companions.sort_by(|(a_index, a), (b_index, b)| {
+        (a.role == CompanionRole::Front, a_index)
+            .cmp(&(b.role == CompanionRole::Front, b_index))
+            .then_with(|| crate::compare_natural(&a.file, &b.file))
+    });
+    companions.into_iter().map(|(_, companion)| companion).collect()
+}
+
+fn atlas_ext(file: &DirFile) -> Option<&str> {
+    if is_image(&file.lower) {
+        return None;
+    }
+    file.lower.rfind(".atlas").map(|idx| &file.name[idx..])
+}
+
+fn skeleton_ext(file: &DirFile) -> Option<&str> {
+    SKELETON_EXTENSIONS
+        .iter()
+        .filter_map(|ext| file.lower.find(ext))
+        .min()
+        .map(|idx| &file.name[idx..])
+}
+
+fn unpaired_skeletons<'a>(ctx: &DetectContext<'a>) -> Vec<(&'a DirFile, &'a str)> {
+    let dir = ctx.dir();
+    ctx.unclaimed()
+        .filter(|f| atlas_ext(f).is_none())
+        .filter_map(|f| Some((f, skeleton_ext(f)?)))
+        .filter(|(f, _)| spine::is_skeleton_file(&dir.join(&f.name)))
+        .collect()
+}
+
+struct LooseImages {
+    found: Vec<(String, String)>,
+    missing: Vec<String>,
+}
+
+struct NameIndex<'a> {
+    dir: &'a Path,
+    folders: HashMap<String, HashMap<String, String>>,
+}
+
+impl<'a> NameIndex<'a> {
+    fn new(dir: &'a Path) -> Self {
+        Self {
+            dir,
+            folders: HashMap::new(),
+        }
+    }
+
+    fn names(&mut self, folder: &str) -> &HashMap<String, String> {
+        let dir = self.dir;
+        self.folders.entry(folder.to_string()).or_insert_with(|| {
+            fs::read_dir(dir.join(folder))
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .map(|name| (scene::normalize_name(&name), name))
+                .collect()
+        })
+    }
+
+    fn find(&mut self, rel_path: &str) -> Option<String> {
+        let mut resolved: Vec<String> = Vec::new();
+        for component in rel_path.split('/').filter(|c| !c.is_empty() && *c != ".") {
+            if component == ".." {
+                resolved.push(component.to_string());
+                continue;
+            }
+            let folder = resolved.join("/");
+            let name = self.names(&folder).get(&scene::normalize_name(component))?.clone();
+            resolved.push(name);
+        }
+        let path = resolved.join("/");
+        self.dir.join(&path).is_file().then_some(path)
+    }
+}
+
+fn find_loose_images(names: &mut NameIndex, skeleton: &SkeletonData) -> Option<LooseImages> {
+    let mut folders = Vec::new();
+    if let Some(images) = skeleton.images.as_deref().map(|p| p.replace('\\', "/"))
+        && !Path::new(&images).is_absolute()
+        && !images.contains(':')
+    {
+        folders.push(images.trim_start_matches("./").trim_end_matches('/').to_string());
+    }
+    folders.extend(["images".to_string(), String::new()]);
+    let mut paths: Vec<&String> = skeleton.region_paths.iter().collect();
+    paths.sort();
+    folders.iter().find_map(|folder| {
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for path in &paths {
+            let base = match folder.as_str() {
+                "" => path.to_string(),
+                folder => format!("{}/{}", folder, path),
+            };
+            let image = IMAGE_EXTENSIONS
+                .iter()
+                .find_map(|ext| names.find(&format!("{}{}", base, ext)));
+            match image {
+                Some(image) => found.push((path.to_string(), image)),
+                None => missing.push(path.to_string()),
+            }
+        }
+        (!found.is_empty()).then_some(LooseImages { found, missing })
+    })
+}
+
+fn cached_atlas(dir: &Path, atlas_file: &str, images: &[(String, String)]) -> Option<(PathBuf, Atlas)> {
+    let path = overlay::find(dir, atlas_file)?;
+    let generated = modified(&path)?;
+    let stale = |image: &String| modified(&dir.join(image)).is_none_or(|m| m > generated);
+    if images.iter().any(|(_, image)| stale(image)) {
+        return None;
+    }
+    let atlas = Atlas::read(&path).ok()?;
+    let regions: HashSet<&str> = atlas
+        .pages
+        .iter()
+        .flat_map(|page| &page.regions)
+        .map(|region| region.name.as_str())
+        .collect();
+    (regions == images.iter().map(|(region, _)| region.as_str()).collect()).then_some((path, atlas))
+}
+
+fn generate_atlas(dir: &Path, atlas_file: &str, images: &[(String, String)]) -> Result<(PathBuf, Atlas), String> {
+    let images = images
+        .iter()
+        .map(|(region, image)| {
+            let decoded = image::open(dir.join(image)).map_err(|e| format!("Failed to read {}: {}", image, e))?;
+            Ok((region.clone(), decoded.to_rgba8()))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let (atlas, pages) = packer::pack_images(&images, packer::page_names(atlas_file), &PackOptions::default())?;
+    let path = packer::write_overlay(dir, atlas_file, &atlas, &pages)?;
+    Ok((path, atlas))
+}
+
+pub(crate) fn pack_loose_images(dir: &Path, scene: &mut SceneData) -> Result<(), String> {
+    if scene.loose_images.is_empty() {
+        return Ok(());
+    }
+    let atlas_file = format!("{}.atlas", &scene.name[scene.name_prefix().len()..]);
+    let images: Vec<(String, String)> = scene.loose_images.clone().into_iter().collect();
+    let (atlas_path, atlas) = match cached_atlas(dir, &atlas_file, &images) {
+        Some(cached) => cached,
+        None => generate_atlas(dir, &atlas_file, &images)?,
+    };
+    scene::apply_generated_atlas(&atlas_file, &atlas_path, atlas, scene);
+    scene.loose_images.clear();
+    Ok(())
+}
+
+fn region_names(dir: &Path, atlas: &DirFile) -> HashSet<String> {
+    Atlas::read(&dir.join(&atlas.name))
+        .map(|atlas| {
+            atlas
+                .pages
+                .into_iter()
+                .flat_map(|page| page.regions)
+                .map(|region| region.name)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn matching_atlases(dir: &Path, skeleton: &DirFile, regions: &[HashSet<String>]) -> Vec<usize> {
+    let Ok(data) = spine::read_skeleton(&dir.join(&skeleton.name)) else {
+        return Vec::new();
+    };
+    let paths = &data.region_paths;
+    let scores: Vec<usize> = regions
+        .iter()
+        .map(|names| paths.iter().filter(|p| names.contains(*p)).count())
+        .collect();
+    let best = scores.iter().copied().max().unwrap_or(0);
+    if best == 0 || best * 2 < paths.len() {
+        return Vec::new();
+    }
+    (0..scores.len()).filter(|i| scores[*i] == best).collect()
+}
+
+fn spine_scene(ctx: &DetectContext, skeleton: &DirFile, main_ext: &str, atlas: &DirFile, atlas_ext: &str) -> SceneData {
+    let dir = ctx.dir();
+    let scene_path = skeleton.scene_path();
+    let mut scene = SceneData::new(
+        SceneKind::Spine,
+        scene_path.trim_end_matches(main_ext).to_string(),
+        main_ext.to_string(),
+        atlas_ext.to_string(),
+    );
+    let atlas_path = atlas.scene_path();
+    let atlas_base = &atlas_path[..atlas_path.len() - atlas_ext.len()];
+    if atlas_base != scene.name {
+        scene.atlas_bases.insert(scene.name.clone(), atlas_base.to_string());
+    }
+    scene::resolve_spine(dir, &skeleton.name, &atlas.name, &mut scene);
+    if ctx.options.include_metadata {
+        scene::read_spine_metadata(dir, &skeleton.name, &mut scene);
+    }
+    scene
+}
+
+impl SpineDetector {
+    fn loose_image_scenes(&self, ctx: &mut DetectContext, scenes: &mut Vec<SceneData>) {
+        let dir = ctx.dir();
+        let mut names = NameIndex::new(dir);
+        for (skeleton, main_ext) in unpaired_skeletons(ctx) {
+            let Ok(data) = spine::read_skeleton(&dir.join(&skeleton.name)) else {
+                continue;
+            };
+            let Some(LooseImages { found: images, missing }) = find_loose_images(&mut names, &data) else {
+                continue;
+            };
+            let scene_path = skeleton.scene_path();
+            let mut scene = SceneData::new(
+                SceneKind::Spine,
+                scene_path[..scene_path.len() - main_ext.len()].to_string(),
+                main_ext.to_string(),
+                ".atlas".to_string(),
+            );
+            let atlas_file = format!("{}.atlas", &skeleton.name[..skeleton.name.len() - main_ext.len()]);
+            let image_files: Vec<String> = images.iter().map(|(_, image)| image.clone()).collect();
+            scene::resolve_generated_spine(dir, &skeleton.name, &image_files, &atlas_file, &mut scene);
+            match cached_atlas(dir, &atlas_file, &images) {
+                Some((atlas_path, atlas)) => scene::apply_generated_atlas(&atlas_file, &atlas_path, atlas, &mut scene),
+                None => scene.loose_images.extend(images),
+            }
+            if !missing.is_empty() {
+                scene.add_diagnostic(
+                    Severity::Warning,
+                    "missingImages",
+                    format!("{} attachment images not found: {}", missing.len(), missing.join(", ")),
+                    Some(scene.scene_path(&skeleton.name)),
+                );
+            }
+            if ctx.options.include_metadata {
+                scene::read_spine_metadata(dir, &skeleton.name, &mut scene);
+            }
+            ctx.claim(&skeleton.name);
+            scenes.push(scene);
+        }
+    }
+
+    fn pair_by_name(&self, ctx: &mut DetectContext, scenes: &mut Vec<SceneData>) {
+        let dir = ctx.dir();
+        let files: Vec<&DirFile> = ctx.unclaimed().collect();
+        for (base_name, atlas_ext) in named_atlases(&files, &ctx.companion_rules) {
+            let base_lower = base_name.to_lowercase();
+            let candidates = find_skeleton(dir, &files, &base_lower);
+            let Some(skeleton) = candidates.first().map(|c| c.file) else {
+                continue;
+            };
+            let main_ext = &candidates[0].main_ext;
+            let atlas_file = format!("{}{}", base_name, atlas_ext);
+            let Some(atlas) = files.iter().find(|f| f.name == atlas_file) else {
+                continue;
+            };
+            let mut scene = spine_scene(ctx, skeleton, main_ext, atlas, &atlas_ext);
+            scene.companions = find_companions(&files, &base_lower, main_ext, &ctx.companion_rules);
+            scene.files = scene.companions.iter().map(|c| c.file.clone()).collect();
+            if candidates.len() > 1 {
+                let names: Vec<&str> = candidates.iter().map(|c| c.file.name.as_str()).collect();
+                scene.add_diagnostic(
+                    Severity::Warning,
+                    "ambiguousSkeleton",
+                    format!("Several skeletons match {}: {}; using {}", atlas_file, names.join(", "), skeleton.name),
+                    Some(scene.scene_path(&skeleton.name)),
+                );
+            }
+            ctx.claim(&skeleton.name);
+            ctx.claim(&atlas_file);
+            for extra in &scene.files {
+                ctx.claim(&format!("{}{}", base_name, extra));
+            }
+            scenes.push(scene);
+        }
+    }
+
+    fn pair_by_content(&self, ctx: &mut DetectContext, scenes: &mut Vec<SceneData>) {
+        let dir = ctx.dir();
+        let skeletons = unpaired_skeletons(ctx);
+        let atlases: Vec<(&DirFile, &str)> = ctx
+            .listing
+            .files
+            .iter()
+            .filter_map(|f| Some((f, atlas_ext(f)?)))
+            .collect();
+        if skeletons.is_empty() || atlases.is_empty() {
+            return;
+        }
+        let single_pair = scenes.is_empty() && skeletons.len() == 1 && atlases.len() == 1;
+        let regions: Vec<HashSet<String>> = atlases.iter().map(|(f, _)| region_names(dir, f)).collect();
+        for (skeleton, main_ext) in skeletons {
+            let mut matches = matching_atlases(dir, skeleton, &regions);
+            if matches.is_empty() && single_pair {
+                matches.push(0);
+            }
+            let Some(&(atlas, atlas_ext)) = matches.first().map(|i| &atlases[*i]) else {
+                continue;
+            };
+            let mut scene = spine_scene(ctx, skeleton, main_ext, atlas, atlas_ext);
+            if matches.len() > 1 {
+                let names: Vec<&str> = matches.iter().map(|i| atlases[*i].0.name.as_str()).collect();
+                scene.add_diagnostic(
+                    Severity::Warning,
+                    "ambiguousAtlas",
+                    format!("Several atlases match {}: {}; using {}", skeleton.name, names.join(", "), atlas.name),
+                    Some(scene.scene_path(&atlas.name)),
+                );
+            }
+            ctx.claim(&skeleton.name);
+            ctx.claim(&atlas.name);
+            scenes.push(scene);
+        }
+    }
+}
+
+impl Detector for SpineDetector {
+    fn detect(&self, ctx: &mut DetectContext) -> Vec<SceneData> {
+        let mut scenes = Vec::new();
+        self.pair_by_name(ctx, &mut scenes);
+        self.pair_by_content(ctx, &mut scenes);
+        self.loose_image_scenes(ctx, &mut scenes);
+        scenes
+    }
+}
@@ -0,0 +1,246 @@
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+
+use crate::atlas::Atlas;
+use crate::overlay;
+use crate::packer;
+use crate::scene::{SceneData, SceneKind, Severity, is_image, modified};
+
+fn is_fresh(cached: &Path, sources: &[PathBuf]) -> bool {
+    let Some(generated) = modified(cached) else {
+        return false;
+    };
+    sources.iter().all(|source| modified(source).is_some_and(|m| m <= generated))
+}
+
+fn cache_prefix(max_size: u32) -> String {
+    format!("limit_{}/", max_size)
+}
+
+fn fit_factor(width: u32, height: u32, max_size: u32) -> Option<f32> {
+    let largest = width.max(height);
+    (largest > max_size).then(|| max_size as f32 / largest as f32)
+}
+
+fn scaled(value: u32, factor: f32) -> u32 {
+    (value as f32 * factor).round() as u32
+}
+
+fn scaled_signed(value: i32, factor: f32) -> i32 {
+    (value as f32 * factor).round() as i32
+}
+
+fn scaled_span(start: u32, len: u32, factor: f32) -> (u32, u32) {
+    let scaled_start = scaled(start, factor);
+    (scaled_start, scaled(start + len, factor).saturating_sub(scaled_start))
+}
+
+fn load(path: &Path) -> Result<RgbaImage, String> {
+    Ok(image::open(path)
+        .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?
+        .to_rgba8())
+}
+
+fn resize_to(image: &RgbaImage, (width, height): (u32, u32)) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image.clone();
+    }
+    image::imageops::resize(image, width, height, image::imageops::FilterType::Triangle)
+}
+
+struct PageResize {
+    name: String,
+    from: (u32, u32),
+    to: (u32, u32),
+    factor: f32,
+}
+
+fn downscale_atlas(atlas: &mut Atlas, plan: &[Option<PageResize>]) {
+    for (page, resize) in atlas.pages.iter_mut().zip(plan) {
+        let Some(resize) = resize else {
+            continue;
+        };
+        let factor = resize.factor;
+        (page.width, page.height) = resize.to;
+        for region in &mut page.regions {
+            let (packed_width, packed_height) = packer::packed_size(region);
+            let (x, packed_width) = scaled_span(region.x, packed_width, factor);
+            let (y, packed_height) = scaled_span(region.y, packed_height, factor);
+            (region.x, region.y) = (x, y);
+            (region.width, region.height) = match region.degrees {
+                90 | 270 => (packed_height, packed_width),
+                _ => (packed_width, packed_height),
+            };
+            region.orig_width = scaled(region.orig_width, factor);
+            region.orig_height = scaled(region.orig_height, factor);
+            region.offset_x = scaled_signed(region.offset_x, factor);
+            region.offset_y = scaled_signed(region.offset_y, factor);
+            for edges in region.split.iter_mut().chain(region.pad.iter_mut()) {
+                *edges = edges.map(|edge| scaled_signed(edge, factor));
+            }
+        }
+    }
+}
+
+fn skeleton_names(scene: &SceneData) -> Vec<String> {
+    if scene.is_merged {
+        return scene.files.clone();
+    }
+    let mut names = vec![scene.name.clone()];
+    for file in &scene.files {
+        if let Some(idx) = file.rfind('.')
+            && [".skel", ".json", ".asset"].contains(&&file[idx..])
+        {
+            names.push(format!("{}{}", scene.name, &file[..idx]));
+        }
+    }
+    names
+}
+
+fn limit_spine(dir: &Path, scene: &mut SceneData, max_size: u32) -> Result<(), String> {
+    if scene.atlas_ext.is_empty() {
+        return Ok(());
+    }
+    let mut atlas_paths: Vec<String> = Vec::new();
+    for name in skeleton_names(scene) {
+        let atlas_base = scene.atlas_bases.get(&name).unwrap_or(&name);
+        let atlas_path = format!("{}{}", atlas_base, scene.atlas_ext);
+        if !atlas_paths.contains(&atlas_path) {
+            atlas_paths.push(atlas_path);
+        }
+    }
+    for atlas_path in atlas_paths {
+        limit_atlas(dir, scene, &atlas_path, max_size)?;
+    }
+    Ok(())
+}
+
+fn limit_atlas(dir: &Path, scene: &mut SceneData, atlas_path: &str, max_size: u32) -> Result<(), String> {
+    let prefix = scene.name_prefix().to_string();
+    let Some(atlas_file) = atlas_path.strip_prefix(&prefix).map(str::to_string) else {
+        return Ok(());
+    };
+    let source = match scene.overlay.get(atlas_path) {
+        Some(path) => PathBuf::from(path),
+        None => dir.join(&atlas_file),
+    };
+    let Ok(mut atlas) = Atlas::read(&source) else {
+        return Ok(());
+    };
+    let source_dir = source.parent().unwrap_or(dir).to_path_buf();
+    let page_paths: Vec<PathBuf> = atlas.pages.iter().map(|page| source_dir.join(&page.name)).collect();
+    let plan: Vec<Option<PageResize>> = atlas
+        .pages
+        .iter()
+        .zip(&page_paths)
+        .map(|(page, path)| {
+            let (width, height) = match (page.width, page.height) {
+                (0, _) | (_, 0) => image::image_dimensions(path).ok()?,
+                size => size,
+            };
+            let factor = fit_factor(width, height, max_size)?;
+            Some(PageResize {
+                name: page.name.clone(),
+                from: (width, height),
+                to: (scaled(width, factor).max(1), scaled(height, factor).max(1)),
+                factor,
+            })
+        })
+        .collect();
+    if plan.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    let cache_file = format!("{}{}", cache_prefix(max_size), atlas_file);
+    let mut sources = page_paths.clone();
+    sources.push(source.clone());
+    let cached = overlay::find(dir, &cache_file).filter(|path| is_fresh(path, &sources));
+    let path = match cached {
+        Some(path) => path,
+        None => {
+            let mut pages = Vec::with_capacity(page_paths.len());
+            for (path, resize) in page_paths.iter().zip(&plan) {
+                let image = load(path)?;
+                pages.push(match resize {
+                    Some(resize) => resize_to(&image, resize.to),
+                    None => image,
+                });
+            }
+            downscale_atlas(&mut atlas, &plan);
+            packer::write_overlay(dir, &cache_file, &atlas, &pages)?
+        }
+    };
+    scene.add_overlay(&atlas_file, &path);
+    let atlas_dir = match atlas_file.rfind('/') {
+        Some(idx) => &atlas_file[..=idx],
+        None => "",
+    };
+    for resize in plan.into_iter().flatten() {
+        scene.add_diagnostic(
+            Severity::Warning,
+            "downscaledTexture",
+            format!(
+                "Atlas page {} is {}x{}, above the GPU limit of {}, using a copy scaled to {}x{}",
+                resize.name, resize.from.0, resize.from.1, max_size, resize.to.0, resize.to.1
+            ),
+            Some(format!("{}{}{}", prefix, atlas_dir, resize.name)),
+        );
+    }
+    Ok(())
+}
+
+fn limit_textures(dir: &Path, scene: &mut SceneData, max_size: u32) -> Result<(), String> {
+    let prefix = scene.name_prefix().to_string();
+    let textures: Vec<String> = scene
+        .assets
+        .iter()
+        .filter(|asset| is_image(asset))
+        .filter_map(|asset| asset.strip_prefix(&prefix).map(str::to_string))
+        .collect();
+    for texture in textures {
+        let source = dir.join(&texture);
+        let Ok((width, height)) = image::image_dimensions(&source) else {
+            continue;
+        };
+        let Some(factor) = fit_factor(width, height, max_size) else {
+            continue;
+        };
+        let target = (scaled(width, factor).max(1), scaled(height, factor).max(1));
+        let mut cache_file = format!("{}{}", cache_prefix(max_size), texture);
+        if !cache_file.to_lowercase().ends_with(".png") {
+            cache_file.push_str(".png");
+        }
+        let cached = overlay::find(dir, &cache_file).filter(|path| is_fresh(path, std::slice::from_ref(&source)));
+        let path = match cached {
+            Some(path) => path,
+            None => overlay::write_image(dir, &cache_file, &resize_to(&load(&source)?, target))?,
+        };
+        scene.add_overlay(&texture, &path);
+        scene.add_diagnostic(
+            Severity::Warning,
+            "downscaledTexture",
+            format!(
+                "Texture {} is {}x{}, above the GPU limit of {}, using a copy scaled to {}x{}",
+                texture, width, height, max_size, target.0, target.1
+            ),
+            Some(format!("{}{}", prefix, texture)),
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn limit_scene(dir: &Path, scene: &mut SceneData, max_size: u32) {
+    let result = match scene.kind {
+        SceneKind::Spine => limit_spine(dir, scene, max_size),
+        SceneKind::Cubism3 | SceneKind::Cubism2 => limit_textures(dir, scene, max_size),
+        SceneKind::LayeredSprite => Ok(()),
+    };
+    if let Err(e) = result {
+        scene.add_diagnostic(
+            Severity::Warning,
+            "downscaleFailed",
+            format!("Failed to downscale oversized textures: {}", e),
+            None,
+        );
+    }
+}
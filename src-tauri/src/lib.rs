@@ -5,15 +5,37 @@ use std::path::Path;
 use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Manager};
 
+mod atlas;
+mod detect;
+mod downscale;
+mod library;
+mod live2d;
+mod moc3;
+mod overlay;
+mod packer;
+mod package;
+mod psd;
+mod scene;
+mod spine;
+mod sprite;
+mod thumbnail;
+mod watcher;
+
+use scene::{ScanOptions, SceneData, SceneKind};
+
 #[derive(Default)]
 struct AppState {
     temp_dirs: Mutex<Vec<tempfile::TempDir>>,
+    watcher: Mutex<Option<watcher::RootWatcher>>,
+    library: Mutex<Option<library::Library>>,
 }
 
 impl AppState {
     fn new() -> Self {
         Self {
             temp_dirs: Mutex::new(Vec::new()),
+            watcher: Mutex::new(None),
+            library: Mutex::new(None),
         }
     }
 }
@@ -25,18 +47,6 @@ fn skip_dir(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-#[derive(serde::Serialize, Clone)]
-struct SceneData {
-    name: String,
-    #[serde(rename = "mainExt")]
-    main_ext: String,
-    #[serde(rename = "atlasExt")]
-    atlas_ext: String,
-    files: Vec<String>,
-    #[serde(rename = "isMerged")]
-    is_merged: bool,
-}
-
 fn create_command(program: &str) -> std::process::Command {
     let mut cmd = std::process::Command::new(program);
     #[cfg(target_os = "windows")]
@@ -165,6 +175,7 @@ fn is_definitely_not_unity_bundle(path: &Path) -> bool {
             "wav" | "mp3" | "ogg" | "flac" | "aac" | "m4a" | "wma" => true,
             "mp4" | "avi" | "mkv" | "mov" | "wmv" | "flv" | "webm" => true,
             "zip" | "7z" | "rar" | "tar" | "gz" | "bz2" | "xz" => true,
+            "apk" | "xapk" | "apks" | "apkm" | "obb" | "ipa" => true,
             "dll" | "exe" | "pdb" | "so" | "dylib" | "bin" => true,
             "html" | "css" | "js" | "ts" => true,
             _ => false,
@@ -174,6 +185,48 @@ fn is_definitely_not_unity_bundle(path: &Path) -> bool {
     }
 }
 
+fn find_unity_bundles(dir: &Path) -> Vec<std::path::PathBuf> {
+    let mut unity_bundles = Vec::new();
+    for file_path in find_all_files(dir) {
+        if is_definitely_not_unity_bundle(&file_path) {
+            continue;
+        }
+        if let Ok(mut f) = fs::File::open(&file_path) {
+            let mut header = [0u8; 8];
+            if let Ok(n) = f.read(&mut header) {
+                if unityfs::is_unity_bundle(&header[..n]) {
+                    unity_bundles.push(file_path);
+                }
+            }
+        }
+    }
+    unity_bundles
+}
+
+fn extract_package_with_bundles(path: &Path, out_dir: &Path, skip_unity: bool) -> Result<bool, String> {
+    package::extract_package(path, out_dir)?;
+    if skip_unity {
+        return Ok(false);
+    }
+    let unity_out_dir = out_dir.join("unity");
+    let mut extracted_any = false;
+    for bundle_path in find_unity_bundles(out_dir) {
+        let group_key = get_model_group_key(&bundle_path);
+        let bundle_out_dir = unity_out_dir.join(group_key);
+        let _ = std::fs::create_dir_all(&bundle_out_dir);
+        let success = match extract_layered_sprite_native(&bundle_path, &bundle_out_dir) {
+            Ok(_) => true,
+            Err(_) => unityfs::extract_unity_assets_from_path(&bundle_path, &bundle_out_dir).is_ok(),
+        };
+        if success {
+            extracted_any = true;
+        } else {
+            eprintln!("Failed to extract bundle {:?}", bundle_path);
+        }
+    }
+    Ok(extracted_any)
+}
+
 fn get_model_group_key(path: &Path) -> String {
     let mut stem = path
         .file_name()
@@ -596,28 +649,18 @@ async fn handle_dropped_path(
     path: String,
     merge_sequential: bool,
     skip_unity: bool,
+    options: Option<ScanOptions>,
     app_handle: AppHandle,
 ) -> Result<HashMap<String, Vec<SceneData>>, String> {
     app_handle.emit("progress", true).unwrap();
+    watcher::stop(&app_handle);
     let path_obj = Path::new(&path);
     if path_obj.is_dir() {
-        let mut unity_bundles = Vec::new();
-        if !skip_unity {
-            let all_files = find_all_files(&path_obj);
-            for file_path in &all_files {
-                if is_definitely_not_unity_bundle(file_path) {
-                    continue;
-                }
-                if let Ok(mut f) = fs::File::open(file_path) {
-                    let mut header = [0u8; 8];
-                    if let Ok(n) = f.read(&mut header) {
-                        if unityfs::is_unity_bundle(&header[..n]) {
-                            unity_bundles.push(file_path.clone());
-                        }
-                    }
-                }
-            }
-        }
+        let unity_bundles = if skip_unity {
+            Vec::new()
+        } else {
+            find_unity_bundles(path_obj)
+        };
         if !unity_bundles.is_empty() {
             let spive_temp_root = std::env::temp_dir().join("spive2d");
             let _ = std::fs::create_dir_all(&spive_temp_root);
@@ -649,7 +692,7 @@ async fn handle_dropped_path(
             if !extracted_any {
                 return Err("Failed to extract any Unity bundles in directory".to_string());
             }
-            let result = get_subdir_files(temp_path, merge_sequential, app_handle.clone());
+            let result = get_subdir_files(temp_path, merge_sequential, options.clone(), app_handle.clone());
             let state = app_handle.state::<AppState>();
             let mut temp_dirs = state.temp_dirs.lock().unwrap();
             temp_dirs.push(temp_dir);
@@ -658,7 +701,11 @@ async fn handle_dropped_path(
             }
             return result;
         } else {
-            get_subdir_files(path, merge_sequential, app_handle)
+            let result = get_subdir_files(path.clone(), merge_sequential, options.clone(), app_handle.clone());
+            if let Ok(scenes) = &result {
+                watcher::start(&app_handle, path_obj, merge_sequential, options.unwrap_or_default(), scenes.clone());
+            }
+            result
         }
     } else if path_obj.is_file() {
         let is_unity = if skip_unity {
@@ -690,7 +737,7 @@ async fn handle_dropped_path(
                     let _ = unityfs::extract_unity_assets_from_path(&path_obj, &bundle_out_dir);
                 }
             }
-            let result = get_subdir_files(temp_path, merge_sequential, app_handle.clone());
+            let result = get_subdir_files(temp_path, merge_sequential, options.clone(), app_handle.clone());
             let state = app_handle.state::<AppState>();
             let mut temp_dirs = state.temp_dirs.lock().unwrap();
             temp_dirs.push(temp_dir);
@@ -725,7 +772,28 @@ async fn handle_dropped_path(
                         final_path = entries[0].path().to_string_lossy().into_owned();
                     }
                 }
-                let result = get_subdir_files(final_path, merge_sequential, app_handle.clone());
+                let result = get_subdir_files(final_path, merge_sequential, options.clone(), app_handle.clone());
+                let state = app_handle.state::<AppState>();
+                let mut temp_dirs = state.temp_dirs.lock().unwrap();
+                temp_dirs.push(temp_dir);
+                if temp_dirs.len() > 2 {
+                    temp_dirs.remove(0);
+                }
+                result
+            }
+            Some(ext) if package::is_package_ext(&ext) => {
+                let spive_temp_root = std::env::temp_dir().join("spive2d");
+                let _ = std::fs::create_dir_all(&spive_temp_root);
+                let temp_dir = tempfile::Builder::new()
+                    .prefix("model_")
+                    .tempdir_in(spive_temp_root)
+                    .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+                let temp_path = temp_dir.path().to_string_lossy().into_owned();
+                if let Err(e) = extract_package_with_bundles(path_obj, temp_dir.path(), skip_unity) {
+                    app_handle.emit("progress", false).unwrap();
+                    return Err(e);
+                }
+                let result = get_subdir_files(temp_path, merge_sequential, options.clone(), app_handle.clone());
                 let state = app_handle.state::<AppState>();
                 let mut temp_dirs = state.temp_dirs.lock().unwrap();
                 temp_dirs.push(temp_dir);
@@ -750,9 +818,11 @@ fn handle_unity_bytes(
     bytes: Vec<u8>,
     merge_sequential: bool,
     skip_unity: bool,
+    options: Option<ScanOptions>,
     app_handle: AppHandle,
 ) -> Result<Option<HashMap<String, Vec<SceneData>>>, String> {
     app_handle.emit("progress", true).unwrap();
+    watcher::stop(&app_handle);
     let header_len = std::cmp::min(bytes.len(), 8);
     let is_unity = unityfs::is_unity_bundle(&bytes[..header_len]);
     if is_unity && !skip_unity {
@@ -784,7 +854,7 @@ fn handle_unity_bytes(
                 let _ = app_handle.emit("progress", false);
                 format!("Failed to extract Unity assets: {}", e)
             })?;
-        let result = get_subdir_files(temp_path, merge_sequential, app_handle.clone());
+        let result = get_subdir_files(temp_path, merge_sequential, options.clone(), app_handle.clone());
         let state = app_handle.state::<AppState>();
         let mut temp_dirs = state.temp_dirs.lock().unwrap();
         temp_dirs.push(temp_dir);
@@ -804,9 +874,11 @@ async fn handle_urls(
     urls: Vec<String>,
     merge_sequential: bool,
     skip_unity: bool,
+    options: Option<ScanOptions>,
     app_handle: AppHandle,
 ) -> Result<HashMap<String, Vec<SceneData>>, String> {
     app_handle.emit("progress", true).unwrap();
+    watcher::stop(&app_handle);
     let spive_temp_root = std::env::temp_dir().join("spive2d");
     let _ = std::fs::create_dir_all(&spive_temp_root);
     let temp_dir = tempfile::Builder::new()
@@ -874,6 +946,16 @@ async fn handle_urls(
                             if let Err(e) = extract_archive(&path_str, temp_dir.path(), &ext_str) {
                                 eprintln!("Failed to extract archive {:?}: {}", dest_path, e);
                             }
+                        } else if package::is_package_ext(&ext_str) {
+                            let stem = Path::new(filename)
+                                .file_stem()
+                                .map(|s| s.to_string_lossy().into_owned())
+                                .unwrap_or_else(|| "package".to_string());
+                            let package_out_dir = temp_dir.path().join(stem);
+                            if let Err(e) = extract_package_with_bundles(&dest_path, &package_out_dir, skip_unity) {
+                                eprintln!("Failed to extract package {:?}: {}", dest_path, e);
+                            }
+                            let _ = std::fs::remove_file(&dest_path);
                         }
                     }
                 }
@@ -884,7 +966,7 @@ async fn handle_urls(
         let _ = app_handle.emit("progress", false);
         return Err("Failed to download or extract any files from the provided URLs".to_string());
     }
-    let result = get_subdir_files(temp_path, merge_sequential, app_handle.clone());
+    let result = get_subdir_files(temp_path, merge_sequential, options.clone(), app_handle.clone());
     let state = app_handle.state::<AppState>();
     let mut temp_dirs = state.temp_dirs.lock().unwrap();
     temp_dirs.push(temp_dir);
@@ -900,11 +982,13 @@ async fn handle_dropped_paths(
     paths: Vec<String>,
     merge_sequential: bool,
     skip_unity: bool,
+    options: Option<ScanOptions>,
     app_handle: AppHandle,
 ) -> Result<HashMap<String, Vec<SceneData>>, String> {
     app_handle.emit("progress", true).unwrap();
+    watcher::stop(&app_handle);
     if paths.len() == 1 {
-        let result = handle_dropped_path(paths[0].clone(), merge_sequential, skip_unity, app_handle.clone()).await;
+        let result = handle_dropped_path(paths[0].clone(), merge_sequential, skip_unity, options.clone(), app_handle.clone()).await;
         let _ = app_handle.emit("progress", false);
         return result;
     }
@@ -925,23 +1009,11 @@ async fn handle_dropped_paths(
             continue;
         }
         if path_obj.is_dir() {
-            let mut unity_bundles = Vec::new();
-            if !skip_unity {
-                let all_files = find_all_files(&path_obj);
-                for file_path in &all_files {
-                    if is_definitely_not_unity_bundle(file_path) {
-                        continue;
-                    }
-                    if let Ok(mut f) = fs::File::open(file_path) {
-                        let mut header = [0u8; 8];
-                        if let Ok(n) = f.read(&mut header) {
-                            if unityfs::is_unity_bundle(&header[..n]) {
-                                unity_bundles.push(file_path.clone());
-                            }
-                        }
-                    }
-                }
-            }
+            let unity_bundles = if skip_unity {
+                Vec::new()
+            } else {
+                find_unity_bundles(path_obj)
+            };
             if !unity_bundles.is_empty() {
                 for bundle_path in unity_bundles {
                     let group_key = get_model_group_key(&bundle_path);
@@ -1000,6 +1072,18 @@ async fn handle_dropped_paths(
                             added_any = true;
                         }
                     }
+                    Some(ext) if package::is_package_ext(&ext) => {
+                        let stem = path_obj
+                            .file_stem()
+                            .map(|s| s.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| "package".to_string());
+                        let package_out_dir = temp_dir.path().join(stem);
+                        if let Err(e) = extract_package_with_bundles(path_obj, &package_out_dir, skip_unity) {
+                            eprintln!("Failed to extract package {:?}: {}", path_obj, e);
+                        } else {
+                            added_any = true;
+                        }
+                    }
                     _ => {
                         if let Some(filename) = path_obj.file_name() {
                             let dest_path = temp_dir.path().join(filename);
@@ -1016,7 +1100,7 @@ async fn handle_dropped_paths(
         let _ = app_handle.emit("progress", false);
         return Err("No valid files or models found in dropped paths".to_string());
     }
-    let result = get_subdir_files(temp_path, merge_sequential, app_handle.clone());
+    let result = get_subdir_files(temp_path, merge_sequential, options.clone(), app_handle.clone());
     let state = app_handle.state::<AppState>();
     let mut temp_dirs = state.temp_dirs.lock().unwrap();
     temp_dirs.push(temp_dir);
@@ -1049,15 +1133,17 @@ fn list_files_recursive(dir: &Path) -> Vec<String> {
 fn get_subdir_files(
     folder_path: String,
     merge_sequential: bool,
+    options: Option<ScanOptions>,
     app_handle: AppHandle,
 ) -> Result<HashMap<String, Vec<SceneData>>, String> {
     let root_path = Path::new(&folder_path);
+    let options = options.unwrap_or_default();
     let mut dir_files_map: HashMap<String, Vec<SceneData>> = HashMap::new();
     if !root_path.exists() || !root_path.is_dir() {
         app_handle.emit("progress", false).unwrap();
         return Ok(dir_files_map);
     }
-    match process_directory_with_subdirs(root_path, root_path, merge_sequential) {
+    match process_directory_with_subdirs(root_path, root_path, merge_sequential, &options) {
         Ok(subdir_map) => {
             dir_files_map.extend(subdir_map);
         }
@@ -1077,6 +1163,153 @@ fn get_subdir_files(
     Ok(dir_files_map)
 }
 
+#[tauri::command]
+fn get_moc3_info(path: String) -> Result<moc3::Moc3Info, String> {
+    live2d::read_moc3_info(Path::new(&path))
+}
+
+#[tauri::command]
+async fn get_scene_thumbnail(
+    dir: String,
+    scene: SceneData,
+    size: Option<u32>,
+) -> Result<String, String> {
+    let size = size.unwrap_or(thumbnail::DEFAULT_THUMBNAIL_SIZE);
+    let path = thumbnail::scene_thumbnail(Path::new(&dir), &scene, size)?;
+    Ok(path.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+}
+
+#[tauri::command]
+async fn prepare_scene(dir: String, mut scene: SceneData) -> Result<SceneData, String> {
+    let scene_dir = Path::new(&dir).join(scene.name_prefix());
+    detect::pack_loose_images(&scene_dir, &mut scene)?;
+    Ok(scene)
+}
+
+#[tauri::command]
+fn get_spine_metadata(path: String) -> Result<spine::SpineMetadata, String> {
+    spine::read_metadata(Path::new(&path))
+}
+
+#[tauri::command]
+async fn render_spine_frames(
+    skeleton: String,
+    atlas: String,
+    options: spine::render::RenderOptions,
+    fps: Option<f32>,
+    output_dir: String,
+) -> Result<Vec<String>, String> {
+    let paths = spine::render::export_frames(
+        Path::new(&skeleton),
+        Path::new(&atlas),
+        &options,
+        fps,
+        Path::new(&output_dir),
+    )?;
+    Ok(paths
+        .iter()
+        .map(|path| path.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+        .collect())
+}
+
+#[tauri::command]
+async fn unpack_atlas(atlas: String, output_dir: String) -> Result<Vec<String>, String> {
+    let paths = packer::unpack_atlas(Path::new(&atlas), Path::new(&output_dir))?;
+    Ok(paths
+        .iter()
+        .map(|path| path.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+        .collect())
+}
+
+#[tauri::command]
+async fn repack_atlas(atlas: String, options: Option<packer::PackOptions>) -> Result<String, String> {
+    let atlas_path = Path::new(&atlas);
+    let (repacked, pages) = packer::repack_atlas(atlas_path, &options.unwrap_or_default())?;
+    let source_dir = atlas_path.parent().unwrap_or(Path::new("."));
+    let atlas_file = atlas_path.file_name().and_then(|n| n.to_str()).ok_or("Invalid atlas path")?;
+    let path = packer::write_overlay(source_dir, atlas_file, &repacked, &pages)?;
+    Ok(path.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+}
+
+#[tauri::command]
+fn revert_repacked_atlas(atlas: String) -> Result<bool, String> {
+    let atlas_path = Path::new(&atlas);
+    let source_dir = atlas_path.parent().unwrap_or(Path::new("."));
+    let atlas_file = atlas_path.file_name().and_then(|n| n.to_str()).ok_or("Invalid atlas path")?;
+    packer::remove_overlay(source_dir, atlas_file)
+}
+
+#[tauri::command]
+async fn export_layered_sprites(paths: Vec<String>, output_dir: String) -> Result<Vec<String>, String> {
+    let sources: Vec<std::path::PathBuf> = paths.iter().map(std::path::PathBuf::from).collect();
+    let written = sprite::export_all_expressions(&sources, Path::new(&output_dir))?;
+    Ok(written
+        .iter()
+        .map(|path| path.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+        .collect())
+}
+
+#[tauri::command]
+async fn export_layered_sprite_psd(path: String, output: String) -> Result<String, String> {
+    let meta_path = Path::new(&path);
+    let meta_dir = meta_path.parent().unwrap_or(Path::new("."));
+    let meta_name = meta_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let mut sprite = sprite::LayeredSprite::open(meta_dir, meta_name)?;
+    sprite.export_psd(Path::new(&output))?;
+    Ok(output)
+}
+
+fn library_scan_options(options: Option<ScanOptions>) -> ScanOptions {
+    ScanOptions {
+        include_metadata: true,
+        fix_in_place: false,
+        read_only: true,
+        max_texture_size: None,
+        ..options.unwrap_or_default()
+    }
+}
+
+#[tauri::command]
+fn get_library_roots(app_handle: AppHandle) -> Result<Vec<library::LibraryRoot>, String> {
+    library::with_library(&app_handle, false, |library| Ok(library.roots()))
+}
+
+#[tauri::command]
+async fn add_library_root(
+    path: String,
+    options: Option<ScanOptions>,
+    app_handle: AppHandle,
+) -> Result<library::IndexSummary, String> {
+    let options = library_scan_options(options);
+    let _ = app_handle.emit("progress", true);
+    let result = library::index_library(&app_handle, &options, |library| library.add_root(Path::new(&path)));
+    let _ = app_handle.emit("progress", false);
+    result
+}
+
+#[tauri::command]
+fn remove_library_root(path: String, app_handle: AppHandle) -> Result<bool, String> {
+    library::with_library(&app_handle, true, |library| Ok(library.remove_root(&path)))
+}
+
+#[tauri::command]
+async fn refresh_library(
+    path: Option<String>,
+    options: Option<ScanOptions>,
+    app_handle: AppHandle,
+) -> Result<library::IndexSummary, String> {
+    let options = library_scan_options(options);
+    let _ = app_handle.emit("progress", true);
+    let result = library::index_library(&app_handle, &options, |library| library.refresh(path.as_deref()));
+    let _ = app_handle.emit("progress", false);
+    result
+}
+
+#[tauri::command]
+fn search_library(query: library::LibraryQuery, app_handle: AppHandle) -> Result<Vec<library::LibraryEntry>, String> {
+    library::with_library(&app_handle, false, |library| Ok(library.search(&query)))
+}
+
 #[tauri::command]
 fn append_to_list(app_handle: AppHandle, text: String) -> Result<(), String> {
     let download_dir = app_handle.path().download_dir().map_err(|e| e.to_string())?;
@@ -1209,465 +1442,174 @@ fn compare_natural(a: &str, b: &str) -> std::cmp::Ordering {
     }
 }
 
+fn dir_key(path: &Path) -> String {
+    let mut key = path
+        .to_string_lossy()
+        .replace(std::path::MAIN_SEPARATOR, "/");
+    if !key.ends_with('/') {
+        key.push('/');
+    }
+    key
+}
+
 fn process_directory_with_subdirs(
     dir_path: &Path,
     base_path: &Path,
     merge_sequential: bool,
+    options: &ScanOptions,
 ) -> Result<HashMap<String, Vec<SceneData>>, String> {
     let mut dir_files_map = HashMap::new();
-    let current_file_groups = process_files(dir_path, base_path, merge_sequential)?;
+    let current_file_groups = process_files(dir_path, base_path, merge_sequential, options)?;
     if !current_file_groups.is_empty() {
-        let mut normalized_path = dir_path
-            .to_string_lossy()
-            .replace(std::path::MAIN_SEPARATOR, "/");
-        if !normalized_path.ends_with('/') {
-            normalized_path.push('/');
-        }
-        dir_files_map.insert(normalized_path, current_file_groups);
+        dir_files_map.insert(dir_key(dir_path), current_file_groups);
     }
     for entry in fs::read_dir(dir_path).map_err(|e| e.to_string())? {
         let entry = entry.map_err(|e| e.to_string())?;
         let entry_path = entry.path();
         if entry_path.is_dir() {
             if skip_dir(&entry_path) { continue }
-            let subdir_file_groups = process_directory(&entry_path, base_path, merge_sequential)?;
+            let subdir_file_groups = process_directory(&entry_path, base_path, merge_sequential, options)?;
             if !subdir_file_groups.is_empty() {
-                let mut normalized_subdir_path = entry_path
-                    .to_string_lossy()
-                    .replace(std::path::MAIN_SEPARATOR, "/");
-                if !normalized_subdir_path.ends_with('/') {
-                    normalized_subdir_path.push('/');
-                }
-                dir_files_map.insert(normalized_subdir_path, subdir_file_groups);
+                dir_files_map.insert(dir_key(&entry_path), subdir_file_groups);
             }
         }
     }
     Ok(dir_files_map)
 }
 
-fn is_live2d_texture_name(name: &str) -> bool {
-    let name_lower = name.to_lowercase();
-    if !name_lower.ends_with(".png") {
-        return false;
-    }
-    let stem = &name_lower[..name_lower.len() - 4];
-    if !stem.starts_with("texture") {
-        return false;
+fn process_files(
+    dir_path: &Path,
+    base_path: &Path,
+    merge_sequential: bool,
+    options: &ScanOptions,
+) -> Result<Vec<SceneData>, String> {
+    let listing = detect::DirListing::read(dir_path, base_path)?;
+    let mut file_groups = detect::detectors().detect(&listing, options);
+    file_groups.sort_unstable_by(|a, b| compare_natural(&a.name, &b.name));
+    if merge_sequential && file_groups.len() > 1 {
+        let limit = options.merge_limit.unwrap_or(scene::DEFAULT_MERGE_LIMIT);
+        file_groups = merge_sequential_scenes(dir_path, file_groups, limit);
     }
-    let rest = if stem.starts_with("texture_") {
-        &stem["texture_".len()..]
-    } else {
-        &stem["texture".len()..]
-    };
-    !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+    Ok(file_groups)
 }
 
-fn auto_generate_model3_json(
-    dir: &Path,
-    moc_file_name: &str,
-    moc_stem: &str,
-    dir_files: &[String],
-) -> Result<(), String> {
-    let mut textures = Vec::new();
-    let mut physics = None;
-    let mut display_info = None;
-    let mut userdata = None;
-    let mut pose = None;
-    let mut expressions = Vec::new();
-    let mut motions = HashMap::new();
-    let textures_dir = dir.join("textures");
-    let motions_dir = dir.join("motions");
-    for filename in dir_files {
-        let filename_lower = filename.to_lowercase();
-        if is_live2d_texture_name(filename) {
-            let src_path = dir.join(filename);
-            let dest_path = textures_dir.join(filename);
-            if src_path.exists() {
-                let _ = fs::create_dir_all(&textures_dir);
-                if let Err(e) = fs::rename(&src_path, &dest_path) {
-                    if fs::copy(&src_path, &dest_path).is_ok() {
-                        let _ = fs::remove_file(&src_path);
-                    } else {
-                        eprintln!("Failed to move texture file: {}", e);
-                    }
-                }
-            }
-            if dest_path.exists() || src_path.exists() {
-                textures.push(format!("textures/{}", filename));
-            }
-        } else if filename_lower.ends_with(".physics3.json") {
-            physics = Some(filename.clone());
-        } else if filename_lower.ends_with(".cdi3.json") {
-            display_info = Some(filename.clone());
-        } else if filename_lower.ends_with(".userdata3.json") {
-            userdata = Some(filename.clone());
-        } else if filename_lower.ends_with(".pose3.json") {
-            pose = Some(filename.clone());
-        } else if filename_lower.ends_with(".exp3.json") {
-            expressions.push(serde_json::json!({
-                "Name": filename.strip_suffix(".exp3.json").unwrap_or(filename).to_string(),
-                "File": filename.clone()
-            }));
-        } else if filename_lower.ends_with(".motion3.json") {
-            let src_path = dir.join(filename);
-            let dest_path = motions_dir.join(filename);
-            if src_path.exists() {
-                let _ = fs::create_dir_all(&motions_dir);
-                if let Err(e) = fs::rename(&src_path, &dest_path) {
-                    if fs::copy(&src_path, &dest_path).is_ok() {
-                        let _ = fs::remove_file(&src_path);
-                    } else {
-                        eprintln!("Failed to move motion file: {}", e);
-                    }
-                }
-            }
-            if dest_path.exists() || src_path.exists() {
-                let group = motions.entry("".to_string()).or_insert_with(Vec::new);
-                group.push(serde_json::json!({
-                    "File": format!("motions/{}", filename)
-                }));
-            }
-        }
-    }
-    textures.sort();
-    let mut file_references = serde_json::json!({
-        "Moc": moc_file_name,
-        "Textures": textures
-    });
-    if let Some(p) = physics {
-        file_references["Physics"] = serde_json::Value::String(p);
-    }
-    if let Some(d) = display_info {
-        file_references["DisplayInfo"] = serde_json::Value::String(d);
-    }
-    if let Some(u) = userdata {
-        file_references["UserData"] = serde_json::Value::String(u);
-    }
-    if let Some(p_pose) = pose {
-        file_references["Pose"] = serde_json::Value::String(p_pose);
-    }
-    if !expressions.is_empty() {
-        file_references["Expressions"] = serde_json::Value::Array(expressions);
+fn sequence_prefix(name: &str) -> Option<&str> {
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if stem.len() == name.len() {
+        return None;
     }
-    if !motions.is_empty() {
-        file_references["Motions"] = serde_json::to_value(motions).unwrap_or(serde_json::Value::Null);
-    }
-    let model3_json = serde_json::json!({
-        "Version": 3,
-        "FileReferences": file_references
-    });
-    let output_path = dir.join(format!("{}.model3.json", moc_stem));
-    let file = fs::File::create(output_path).map_err(|e| e.to_string())?;
-    serde_json::to_writer_pretty(file, &model3_json).map_err(|e| e.to_string())?;
-    Ok(())
+    Some(stem.trim_end_matches(['_', '-', ' ', '.']))
 }
 
-fn process_files(dir_path: &Path, base_path: &Path, merge_sequential: bool) -> Result<Vec<SceneData>, String> {
-    let mut file_groups = Vec::new();
-    let mut atlas_bases = HashSet::with_capacity(64);
-    let mut atlas_original_extensions: HashMap<String, String> = HashMap::with_capacity(64);
-    let mut file_paths = HashMap::with_capacity(256);
-    let mut all_atlas_info: HashMap<String, String> = HashMap::with_capacity(64);
-    let mut dir_files = Vec::new();
-    let mut moc3_files = Vec::new();
-    let mut moc_files = Vec::new();
-    let mut meta_json_files = Vec::new();
-    let mut has_meta_json = false;
-    let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let entry_path = entry.path();
-        if !entry_path.is_file() {
+fn merge_sequential_scenes(dir_path: &Path, scenes: Vec<SceneData>, limit: usize) -> Vec<SceneData> {
+    let mut groups: Vec<Vec<SceneData>> = Vec::new();
+    let mut result = Vec::new();
+    for scene in scenes {
+        let Some(prefix) = sequence_prefix(&scene.name)
+            .filter(|_| scene.kind == SceneKind::Spine && scene.loose_images.is_empty())
+        else {
+            result.push(scene);
             continue;
-        }
-        let filename = match entry_path.file_name().and_then(|f| f.to_str()) {
-            Some(name) => name,
-            None => continue,
         };
-        dir_files.push(filename.to_string());
-        let relative_path = entry_path
-            .strip_prefix(base_path)
-            .map(|p| {
-                p.to_string_lossy()
-                    .into_owned()
-                    .replace(std::path::MAIN_SEPARATOR, "/")
-            })
-            .unwrap_or_else(|_| filename.to_string());
-        let filename_lower = filename.to_lowercase();
-        file_paths.insert(filename_lower.clone(), relative_path.clone());
-        if filename_lower.contains(".atlas") {
-            let is_image = filename_lower.ends_with(".png") || filename_lower.ends_with(".jpg") || filename_lower.ends_with(".jpeg") || filename_lower.ends_with(".webp");
-            if !is_image {
-                if let Some(idx) = filename_lower.rfind(".atlas") {
-                    let base_name_part = &filename[..idx];
-                    let extension_part = &filename[idx..];
-                    all_atlas_info.insert(base_name_part.to_string(), extension_part.to_string());
-                }
-            }
-        } else if filename_lower.contains(".moc3") {
-            moc3_files.push((filename.to_string(), relative_path.clone()));
-        } else if filename_lower.contains(".moc") && !filename_lower.contains(".moc3") {
-            moc_files.push((filename.to_string(), relative_path.clone()));
-        } else if filename_lower == "meta.json" {
-            has_meta_json = true;
-        } else if filename_lower.ends_with(".meta.json") {
-            meta_json_files.push(filename.to_string());
+        let group = groups.iter_mut().find(|members| {
+            let first = &members[0];
+            sequence_prefix(&first.name) == Some(prefix)
+                && first.kind == scene.kind
+                && first.main_ext == scene.main_ext
+                && first.atlas_ext == scene.atlas_ext
+        });
+        match group {
+            Some(members) => members.push(scene),
+            None => groups.push(vec![scene]),
         }
     }
-    for (filename, relative_path) in moc3_files {
-        let filename_lower = filename.to_lowercase();
-        let adjusted_path = if let Some(slash_pos) = relative_path.find('/') {
-            &relative_path[slash_pos + 1..]
+    for mut members in groups {
+        let overflow = if limit > 0 && members.len() > limit {
+            members.split_off(limit)
         } else {
-            &relative_path
+            Vec::new()
         };
-        if let Some(moc3_pos) = filename_lower.find(".moc3") {
-            let moc_stem = &filename[..moc3_pos];
-            let model3_json_path = dir_path.join(format!("{}.model3.json", moc_stem));
-            if !model3_json_path.exists() {
-                let _ = auto_generate_model3_json(dir_path, &filename, moc_stem, &dir_files);
-            }
-            let base_name_part =
-                &adjusted_path[..adjusted_path.len() - (filename.len() - moc3_pos)];
-            let extension_part = &filename[moc3_pos..];
-            file_groups.push(SceneData {
-                name: base_name_part.to_string(),
-                main_ext: extension_part.to_string(),
-                atlas_ext: "".to_string(),
-                files: Vec::new(),
-                is_merged: false,
-            });
-        }
-    }
-    for (filename, relative_path) in moc_files {
-        let filename_lower = filename.to_lowercase();
-        let adjusted_path = if let Some(slash_pos) = relative_path.find('/') {
-            &relative_path[slash_pos + 1..]
+        if members.len() > 1 {
+            result.push(merge_scenes(dir_path, members, &overflow));
         } else {
-            &relative_path
-        };
-        if let Some(moc_pos) = filename_lower.find(".moc") {
-            let base_name_part =
-                &adjusted_path[..adjusted_path.len() - (filename.len() - moc_pos)];
-            let extension_part = &filename[moc_pos..];
-            file_groups.push(SceneData {
-                name: base_name_part.to_string(),
-                main_ext: extension_part.to_string(),
-                atlas_ext: "".to_string(),
-                files: Vec::new(),
-                is_merged: false,
-            });
+            result.extend(members);
         }
+        result.extend(overflow);
     }
-    if has_meta_json {
-        file_groups.push(SceneData {
-            name: "meta".to_string(),
-            main_ext: ".json".to_string(),
-            atlas_ext: "".to_string(),
-            files: Vec::new(),
-            is_merged: false,
-        });
-    }
-    for meta_filename in meta_json_files {
-        let stem = meta_filename.strip_suffix(".meta.json")
-            .or_else(|| meta_filename.strip_suffix(".META.JSON"))
-            .unwrap_or(&meta_filename);
-        file_groups.push(SceneData {
-            name: stem.to_string(),
-            main_ext: ".meta.json".to_string(),
-            atlas_ext: "".to_string(),
-            files: Vec::new(),
-            is_merged: false,
-        });
-    }
-    let mut potential_extra_atlases = HashSet::new();
-    for (base_name, extension) in &all_atlas_info {
-        let base_name_lower = base_name.to_lowercase();
-        if base_name_lower.contains("_bg") || base_name_lower.contains("_fg") {
-            potential_extra_atlases.insert(base_name.clone());
-        } else {
-            atlas_bases.insert(base_name.clone());
-            atlas_original_extensions.insert(base_name.clone(), extension.clone());
-        }
-    }
-    for extra_base_name in potential_extra_atlases {
-        let extra_base_name_lower = extra_base_name.to_lowercase();
-        for suffix in ["_bg", "_fg"] {
-            if let Some(pos) = extra_base_name_lower.rfind(suffix) {
-                let corresponding_base_name_lower = &extra_base_name_lower[..pos];
-                let has_corresponding_normal_atlas = atlas_bases
-                    .iter()
-                    .any(|b| b.to_lowercase() == *corresponding_base_name_lower);
-                if !has_corresponding_normal_atlas {
-                    atlas_bases.insert(extra_base_name.clone());
-                    if let Some(extension) = all_atlas_info.get(&extra_base_name) {
-                        atlas_original_extensions.insert(extra_base_name.clone(), extension.clone());
-                    }
-                }
-                break;
-            }
-        }
-    }
-    for base_name in &atlas_bases {
-        let base_lower = base_name.to_lowercase();
-        let atlas_extension = atlas_original_extensions
-            .get(base_name)
-            .cloned()
-            .unwrap_or_default();
-        let mut main_file_info: Option<(String, String, &str)> = None;
-        let target_pattern_skel = format!("{}.skel", base_lower);
-        for (filename_lower, rp) in &file_paths {
-            if let Some(pos) = filename_lower.find(&target_pattern_skel) {
-                if let Some(original_fn) = Path::new(rp).file_name().and_then(|f| f.to_str()) {
-                    let ext_start = pos + base_lower.len();
-                    if ext_start <= original_fn.len() {
-                        let ext_part = &original_fn[ext_start..];
-                        main_file_info = Some((rp.clone(), ext_part.to_string(), "skel"));
-                        break;
-                    }
-                }
-            }
-        }
-        if main_file_info.is_none() {
-            let target_pattern_json = format!("{}.json", base_lower);
-            for (filename_lower, rp) in &file_paths {
-                if let Some(pos) = filename_lower.find(&target_pattern_json) {
-                    if let Some(original_fn) = Path::new(rp).file_name().and_then(|f| f.to_str()) {
-                        let ext_start = pos + base_lower.len();
-                        if ext_start <= original_fn.len() {
-                            let ext_part = &original_fn[ext_start..];
-                            main_file_info = Some((rp.clone(), ext_part.to_string(), "json"));
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-        if main_file_info.is_none() {
-            let target_pattern_asset = format!("{}.asset", base_lower);
-            for (filename_lower, rp) in &file_paths {
-                if let Some(pos) = filename_lower.find(&target_pattern_asset) {
-                    if let Some(original_fn) = Path::new(rp).file_name().and_then(|f| f.to_str()) {
-                        let ext_start = pos + base_lower.len();
-                        if ext_start <= original_fn.len() {
-                            let ext_part = &original_fn[ext_start..];
-                            main_file_info = Some((rp.clone(), ext_part.to_string(), "asset"));
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-        if let Some((main_path, main_extension, _file_type)) = main_file_info {
-            let base_name_for_group = main_path.trim_end_matches(&main_extension).to_string();
-            let adjusted_base_name = if let Some(slash_pos) = base_name_for_group.find('/') {
-                &base_name_for_group[slash_pos + 1..]
-            } else {
-                &base_name_for_group
-            };
-            let mut bg_files = find_extra_files(&base_lower, "_bg", &file_paths, &main_extension);
-            let fg_files = find_extra_files(&base_lower, "_fg", &file_paths, &main_extension);
-            bg_files.extend(fg_files);
-            file_groups.push(SceneData {
-                name: adjusted_base_name.to_string(),
-                main_ext: main_extension,
-                atlas_ext: atlas_extension,
-                files: bg_files,
-                is_merged: false,
-            });
-        }
-    }
-    file_groups.sort_unstable_by(|a, b| compare_natural(&a.name, &b.name));
-    if merge_sequential && file_groups.len() > 1 {
-        if file_groups.len() > 20 {
-            file_groups.truncate(20);
-        }
-        let mut main_ext = String::new();
-        let mut atlas_ext = String::new();
-        let mut all_bases = Vec::new();
-        let mut is_compatible = true;
-        for group in &file_groups {
-            if main_ext.is_empty() {
-                main_ext = group.main_ext.clone();
-                atlas_ext = group.atlas_ext.clone();
-            } else if main_ext != group.main_ext || atlas_ext != group.atlas_ext {
-                is_compatible = false;
-                break;
-            }
-            all_bases.push(group.name.clone());
-            for extra in &group.files {
-                let stem = Path::new(extra)
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or(extra);
-                all_bases.push(format!("{}{}", group.name, stem));
-            }
-        }
-        if is_compatible && !all_bases.is_empty() {
-            let folder_name = dir_path
-                .file_name()
-                .and_then(|f| f.to_str())
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| {
-                    all_bases[0]
-                        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '_' || c == '-')
-                        .to_string()
-                });
-            return Ok(vec![SceneData {
-                name: folder_name,
-                main_ext,
-                atlas_ext,
-                files: all_bases,
-                is_merged: true,
-            }]);
-        }
-    }
-    Ok(file_groups)
+    result.sort_unstable_by(|a, b| compare_natural(&a.name, &b.name));
+    result
 }
 
-fn find_extra_files(
-    base_name_lower: &str,
-    suffix: &str,
-    file_paths: &HashMap<String, String>,
-    main_model_extension: &str,
-) -> Vec<String> {
-    let mut extra_files = Vec::new();
-    let prefix_for_match = format!("{}{}", base_name_lower, suffix);
-    let main_model_extension_lower = main_model_extension.to_lowercase();
-    for (filename_lower, path) in file_paths {
-        if filename_lower.starts_with(&prefix_for_match)
-            && filename_lower.ends_with(&main_model_extension_lower)
-        {
-            let stem_part_lower =
-                &filename_lower[..filename_lower.len() - main_model_extension_lower.len()];
-            if stem_part_lower.starts_with(&prefix_for_match)
-                && stem_part_lower.len() >= prefix_for_match.len()
-            {
-                let filename_part_original_case = if let Some(last_slash) = path.rfind('/') {
-                    &path[last_slash + 1..]
-                } else {
-                    path
-                };
-                if base_name_lower.len() <= filename_part_original_case.len() {
-                    extra_files.push(filename_part_original_case[base_name_lower.len()..].to_string());
-                }
+fn merge_scenes(dir_path: &Path, members: Vec<SceneData>, overflow: &[SceneData]) -> SceneData {
+    let first = &members[0];
+    let prefix = first.name_prefix();
+    let stem = sequence_prefix(&first.name).unwrap_or(&first.name)[prefix.len()..].to_string();
+    let name = if stem.is_empty() {
+        let folder_name = dir_path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or_default();
+        format!("{}{}", prefix, folder_name)
+    } else {
+        format!("{}{}", prefix, stem)
+    };
+    let mut scene = SceneData::new(first.kind, name, first.main_ext.clone(), first.atlas_ext.clone());
+    scene.version = first.version.clone();
+    scene.is_merged = true;
+    for group in &members {
+        scene.files.push(group.name.clone());
+        scene.assets.extend(group.assets.iter().cloned());
+        scene.diagnostics.extend(group.diagnostics.iter().cloned());
+        scene.atlas_pages.extend(group.atlas_pages.iter().cloned());
+        scene.atlas_bases.extend(group.atlas_bases.clone());
+        scene.overlay.extend(group.overlay.clone());
+        for extra in &group.files {
+            let stem = Path::new(extra)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(extra);
+            let base = format!("{}{}", group.name, stem);
+            if let Some(companion) = group.companions.iter().find(|c| c.file == *extra) {
+                scene.companions.push(scene::Companion {
+                    file: base.clone(),
+                    role: companion.role,
+                });
             }
+            scene.files.push(base);
         }
     }
-    extra_files.sort_unstable_by(|a, b| compare_natural(a, b));
-    extra_files
+    if !overflow.is_empty() {
+        let names: Vec<&str> = overflow.iter().map(|s| s.name.as_str()).collect();
+        scene.add_diagnostic(
+            scene::Severity::Warning,
+            "mergeLimit",
+            format!(
+                "Merged the first {} of {} scenes; listing {} separately",
+                members.len(),
+                members.len() + overflow.len(),
+                names.join(", ")
+            ),
+            None,
+        );
+    }
+    scene
 }
 
-fn process_directory(dir_path: &Path, base_path: &Path, merge_sequential: bool) -> Result<Vec<SceneData>, String> {
+fn process_directory(
+    dir_path: &Path,
+    base_path: &Path,
+    merge_sequential: bool,
+    options: &ScanOptions,
+) -> Result<Vec<SceneData>, String> {
     let mut all_file_groups = Vec::new();
-    let current_file_groups = process_files(dir_path, base_path, merge_sequential)?;
+    let current_file_groups = process_files(dir_path, base_path, merge_sequential, options)?;
     all_file_groups.extend(current_file_groups);
     for entry in fs::read_dir(dir_path).map_err(|e| e.to_string())? {
         let entry = entry.map_err(|e| e.to_string())?;
         let entry_path = entry.path();
         if entry_path.is_dir() {
             if skip_dir(&entry_path) { continue }
-            let subdir_file_groups = process_directory(&entry_path, base_path, merge_sequential)?;
+            let subdir_file_groups = process_directory(&entry_path, base_path, merge_sequential, options)?;
             all_file_groups.extend(subdir_file_groups);
         }
     }
@@ -1697,6 +1639,21 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             get_subdir_files,
+            prepare_scene,
+            get_spine_metadata,
+            render_spine_frames,
+            unpack_atlas,
+            repack_atlas,
+            revert_repacked_atlas,
+            export_layered_sprites,
+            export_layered_sprite_psd,
+            get_moc3_info,
+            get_scene_thumbnail,
+            get_library_roots,
+            add_library_root,
+            remove_library_root,
+            refresh_library,
+            search_library,
             handle_dropped_path,
             handle_dropped_paths,
             handle_unity_bytes,
@@ -0,0 +1,431 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use tauri::{AppHandle, Manager};
+
+use crate::scene::{ScanOptions, SceneData, SceneKind, is_image};
+
+const LIBRARY_FILE: &str = "library.json";
+const LIBRARY_VERSION: u32 = 1;
+const DEFAULT_SEARCH_LIMIT: usize = 500;
+const MOTION_EXTENSIONS: &[&str] = &[".motion3.json", ".mtn"];
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct TextureSize {
+    pub(crate) name: String,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LibraryEntry {
+    pub(crate) dir: String,
+    pub(crate) name: String,
+    pub(crate) kind: SceneKind,
+    pub(crate) version: Option<String>,
+    pub(crate) main_ext: String,
+    pub(crate) atlas_ext: String,
+    pub(crate) source: String,
+    pub(crate) animations: Vec<String>,
+    pub(crate) skins: Vec<String>,
+    pub(crate) textures: Vec<TextureSize>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LibraryRoot {
+    pub(crate) path: String,
+    pub(crate) indexed_at: u64,
+    pub(crate) scene_count: usize,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct DirStamp {
+    file_count: usize,
+    mtime: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+struct IndexedDir {
+    root: String,
+    stamp: DirStamp,
+    entries: Vec<LibraryEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct Library {
+    version: u32,
+    roots: Vec<LibraryRoot>,
+    dirs: BTreeMap<String, IndexedDir>,
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        Self {
+            version: LIBRARY_VERSION,
+            roots: Vec::new(),
+            dirs: BTreeMap::new(),
+        }
+    }
+}
+
+pub(crate) struct RootSnapshot {
+    root: String,
+    dirs: BTreeMap<String, IndexedDir>,
+}
+
+pub(crate) struct RootIndex {
+    root: String,
+    dirs: BTreeMap<String, IndexedDir>,
+    summary: IndexSummary,
+}
+
+#[derive(serde::Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndexSummary {
+    pub(crate) scanned_dirs: usize,
+    pub(crate) reused_dirs: usize,
+    pub(crate) removed_dirs: usize,
+    pub(crate) scene_count: usize,
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct LibraryQuery {
+    pub(crate) name: Option<String>,
+    pub(crate) animation: Option<String>,
+    pub(crate) skin: Option<String>,
+    pub(crate) kind: Option<SceneKind>,
+    pub(crate) folder: Option<String>,
+    pub(crate) limit: Option<usize>,
+}
+
+fn mtime_ms(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn strip_extension<'a>(name: &'a str, extensions: &[&str]) -> Option<&'a str> {
+    let lower = name.to_lowercase();
+    extensions
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map(|ext| &name[..name.len() - ext.len()])
+}
+
+impl LibraryEntry {
+    fn new(dir: &Path, scene: &SceneData) -> Self {
+        let dir_key = crate::dir_key(dir);
+        let mut animations = Vec::new();
+        let mut skins = Vec::new();
+        if let Some(metadata) = &scene.metadata {
+            animations.extend(metadata.animations.iter().map(|a| a.name.clone()));
+            skins.extend(metadata.skins.iter().cloned());
+        }
+        for asset in &scene.assets {
+            let file_name = asset.rsplit('/').next().unwrap_or(asset);
+            if let Some(stem) = strip_extension(file_name, MOTION_EXTENSIONS)
+                && !animations.iter().any(|a| a == stem)
+            {
+                animations.push(stem.to_string());
+            }
+        }
+        let mut textures: Vec<TextureSize> = scene
+            .atlas_pages
+            .iter()
+            .map(|page| TextureSize {
+                name: page.name.clone(),
+                width: page.width,
+                height: page.height,
+            })
+            .collect();
+        if textures.is_empty() {
+            for asset in &scene.assets {
+                if !is_image(asset) {
+                    continue;
+                }
+                if let Ok((width, height)) = image::image_dimensions(dir.join(asset)) {
+                    textures.push(TextureSize {
+                        name: asset.clone(),
+                        width,
+                        height,
+                    });
+                }
+            }
+        }
+        Self {
+            source: format!("{}{}{}", dir_key, scene.name, scene.main_ext),
+            dir: dir_key,
+            name: scene.name.clone(),
+            kind: scene.kind,
+            version: scene.version.clone(),
+            main_ext: scene.main_ext.clone(),
+            atlas_ext: scene.atlas_ext.clone(),
+            animations,
+            skins,
+            textures,
+        }
+    }
+
+    fn matches(&self, query: &LibraryQuery) -> bool {
+        query.kind.is_none_or(|kind| kind == self.kind)
+            && query
+                .name
+                .as_deref()
+                .is_none_or(|name| contains_ignore_case(&self.name, name))
+            && query
+                .folder
+                .as_deref()
+                .is_none_or(|folder| contains_ignore_case(&self.dir, folder))
+            && query.animation.as_deref().is_none_or(|animation| {
+                self.animations
+                    .iter()
+                    .any(|a| contains_ignore_case(a, animation))
+            })
+            && query
+                .skin
+                .as_deref()
+                .is_none_or(|skin| self.skins.iter().any(|s| contains_ignore_case(s, skin)))
+    }
+}
+
+fn read_stamp(dir: &Path, subdirs: &mut Vec<PathBuf>) -> Option<DirStamp> {
+    let mut stamp = DirStamp::default();
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if metadata.is_dir() {
+            if !crate::skip_dir(&path) {
+                subdirs.push(path);
+            }
+        } else {
+            stamp.file_count += 1;
+        }
+        stamp.mtime = stamp.mtime.max(mtime_ms(&metadata));
+    }
+    Some(stamp)
+}
+
+fn index_dir(dir: &Path, options: &ScanOptions) -> Vec<LibraryEntry> {
+    let base = dir.parent().unwrap_or(dir);
+    match crate::process_files(dir, base, false, options) {
+        Ok(scenes) => scenes.iter().map(|scene| LibraryEntry::new(dir, scene)).collect(),
+        Err(e) => {
+            eprintln!("Failed to index {}: {}", dir.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+impl RootSnapshot {
+    fn index(mut self, options: &ScanOptions) -> RootIndex {
+        let mut summary = IndexSummary::default();
+        let mut dirs = BTreeMap::new();
+        let mut pending = vec![PathBuf::from(&self.root)];
+        while let Some(dir) = pending.pop() {
+            let Some(stamp) = read_stamp(&dir, &mut pending) else {
+                continue;
+            };
+            let key = crate::dir_key(&dir);
+            if let Some(indexed) = self.dirs.remove(&key)
+                && indexed.stamp == stamp
+            {
+                summary.reused_dirs += 1;
+                dirs.insert(key, indexed);
+                continue;
+            }
+            summary.scanned_dirs += 1;
+            let entries = if stamp.file_count > 0 {
+                index_dir(&dir, options)
+            } else {
+                Vec::new()
+            };
+            dirs.insert(
+                key,
+                IndexedDir {
+                    root: self.root.clone(),
+                    stamp,
+                    entries,
+                },
+            );
+        }
+        summary.removed_dirs = self.dirs.len();
+        summary.scene_count = dirs.values().map(|dir| dir.entries.len()).sum();
+        RootIndex {
+            root: self.root,
+            dirs,
+            summary,
+        }
+    }
+}
+
+impl Library {
+    fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<Library>(&data).ok())
+            .filter(|library| library.version == LIBRARY_VERSION)
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create library directory: {}", e))?;
+        }
+        let data = serde_json::to_vec(self).map_err(|e| e.to_string())?;
+        fs::write(path, data).map_err(|e| format!("Failed to write library: {}", e))
+    }
+
+    pub(crate) fn roots(&self) -> Vec<LibraryRoot> {
+        self.roots.clone()
+    }
+
+    pub(crate) fn add_root(&mut self, root: &Path) -> Result<Vec<RootSnapshot>, String> {
+        if !root.is_dir() {
+            return Err(format!("Invalid path: {}", root.display()));
+        }
+        let root_key = crate::dir_key(root);
+        if let Some(existing) = self
+            .roots
+            .iter()
+            .find(|r| root_key.starts_with(&r.path) && r.path != root_key)
+        {
+            return Err(format!("Folder is already part of the library root {}", existing.path));
+        }
+        let nested: Vec<String> = self
+            .roots
+            .iter()
+            .filter(|r| r.path.starts_with(&root_key))
+            .map(|r| r.path.clone())
+            .collect();
+        for path in &nested {
+            self.remove_root(path);
+        }
+        self.roots.push(LibraryRoot {
+            path: root_key.clone(),
+            indexed_at: 0,
+            scene_count: 0,
+        });
+        Ok(vec![self.snapshot(&root_key)])
+    }
+
+    pub(crate) fn remove_root(&mut self, path: &str) -> bool {
+        let root_key = crate::dir_key(Path::new(path));
+        let before = self.roots.len();
+        self.roots.retain(|r| r.path != root_key);
+        self.dirs.retain(|_, dir| dir.root != root_key);
+        self.roots.len() != before
+    }
+
+    pub(crate) fn refresh(&self, path: Option<&str>) -> Result<Vec<RootSnapshot>, String> {
+        let roots: Vec<String> = match path {
+            Some(path) => {
+                let root_key = crate::dir_key(Path::new(path));
+                if !self.roots.iter().any(|r| r.path == root_key) {
+                    return Err(format!("Folder is not in the library: {}", path));
+                }
+                vec![root_key]
+            }
+            None => self.roots.iter().map(|r| r.path.clone()).collect(),
+        };
+        Ok(roots.iter().map(|root_key| self.snapshot(root_key)).collect())
+    }
+
+    fn snapshot(&self, root_key: &str) -> RootSnapshot {
+        RootSnapshot {
+            root: root_key.to_string(),
+            dirs: self
+                .dirs
+                .iter()
+                .filter(|(_, dir)| dir.root == root_key)
+                .map(|(key, dir)| (key.clone(), dir.clone()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, indexes: Vec<RootIndex>) -> IndexSummary {
+        let mut summary = IndexSummary::default();
+        for index in indexes {
+            let Some(root) = self.roots.iter_mut().find(|r| r.path == index.root) else {
+                continue;
+            };
+            root.indexed_at = now_ms();
+            root.scene_count = index.summary.scene_count;
+            self.dirs.retain(|_, dir| dir.root != index.root);
+            self.dirs.extend(index.dirs);
+            summary.scanned_dirs += index.summary.scanned_dirs;
+            summary.reused_dirs += index.summary.reused_dirs;
+            summary.removed_dirs += index.summary.removed_dirs;
+            summary.scene_count += index.summary.scene_count;
+        }
+        summary
+    }
+
+    pub(crate) fn search(&self, query: &LibraryQuery) -> Vec<LibraryEntry> {
+        let mut results: Vec<LibraryEntry> = self
+            .dirs
+            .values()
+            .flat_map(|dir| dir.entries.iter())
+            .filter(|entry| entry.matches(query))
+            .cloned()
+            .collect();
+        results.sort_by(|a, b| {
+            crate::compare_natural(&a.dir, &b.dir).then_with(|| crate::compare_natural(&a.name, &b.name))
+        });
+        results.truncate(query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+        results
+    }
+}
+
+fn library_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(LIBRARY_FILE))
+}
+
+pub(crate) fn with_library<T>(
+    app_handle: &AppHandle,
+    modify: bool,
+    f: impl FnOnce(&mut Library) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = library_path(app_handle)?;
+    let state = app_handle.state::<crate::AppState>();
+    let mut guard = state.library.lock().unwrap();
+    let library = guard.get_or_insert_with(|| Library::load(&path));
+    let result = f(library)?;
+    if modify {
+        library.save(&path)?;
+    }
+    Ok(result)
+}
+
+pub(crate) fn index_library(
+    app_handle: &AppHandle,
+    options: &ScanOptions,
+    prepare: impl FnOnce(&mut Library) -> Result<Vec<RootSnapshot>, String>,
+) -> Result<IndexSummary, String> {
+    let snapshots = with_library(app_handle, false, prepare)?;
+    let indexes: Vec<RootIndex> = snapshots.into_iter().map(|snapshot| snapshot.index(options)).collect();
+    with_library(app_handle, true, |library| Ok(library.apply(indexes)))
+}
@@ -0,0 +1,381 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value, json};
+
+use crate::moc3::{self, Moc3Info};
+use crate::overlay;
+
+const EYE_BLINK_PARAMETERS: &[&str] = &["parameyelopen", "parameyeropen"];
+const LIP_SYNC_PARAMETERS: &[&str] = &["parammouthopeny"];
+
+pub(crate) fn is_live2d_texture_name(name: &str) -> bool {
+    let name_lower = name.to_lowercase();
+    if !name_lower.ends_with(".png") {
+        return false;
+    }
+    let stem = &name_lower[..name_lower.len() - 4];
+    let Some(rest) = stem
+        .strip_prefix("texture_")
+        .or_else(|| stem.strip_prefix("texture"))
+    else {
+        return false;
+    };
+    !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+}
+
+pub(crate) fn strip_suffix_ignore_case<'a>(name: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = name.len().checked_sub(suffix.len())?;
+    (name.is_char_boundary(split) && name[split..].eq_ignore_ascii_case(suffix)).then(|| &name[..split])
+}
+
+pub(crate) fn motion_group(stem: &str) -> &str {
+    stem.trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_end_matches(['_', '-', '.', ' '])
+}
+
+pub(crate) fn find_companion<'a>(
+    files: &'a [String],
+    suffix: &str,
+    moc_stem: &str,
+    moc_stems: &[&str],
+) -> Option<&'a String> {
+    let candidates: Vec<(&String, String)> = files
+        .iter()
+        .filter_map(|f| Some((f, strip_suffix_ignore_case(f, suffix)?.to_lowercase())))
+        .collect();
+    let moc_stem_lower = moc_stem.to_lowercase();
+    if let Some((file, _)) = candidates.iter().find(|(_, stem)| *stem == moc_stem_lower) {
+        return Some(file);
+    }
+    match candidates.as_slice() {
+        [(file, stem)] if !moc_stems.iter().any(|m| m.to_lowercase() == *stem) => Some(file),
+        _ => None,
+    }
+}
+
+fn normalize_parameter_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn read_cdi3(path: &Path) -> Option<Value> {
+    let content = fs::read(path).ok()?;
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&content);
+    serde_json::from_slice(content).ok()
+}
+
+fn cdi3_entries<'a>(cdi: &'a Value, key: &str) -> impl Iterator<Item = (&'a str, &'a Value)> {
+    cdi.get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some((entry.get("Id")?.as_str()?, entry)))
+}
+
+pub(crate) fn cdi3_parameter_ids(path: &Path) -> Vec<String> {
+    let Some(cdi) = read_cdi3(path) else {
+        return Vec::new();
+    };
+    cdi3_entries(&cdi, "Parameters")
+        .map(|(id, _)| id.to_string())
+        .collect()
+}
+
+fn apply_display_info(info: &mut Moc3Info, cdi: &Value) {
+    let text = |entry: &Value, key: &str| entry.get(key).and_then(Value::as_str).map(str::to_string);
+    let groups: HashMap<&str, &Value> = cdi3_entries(cdi, "ParameterGroups").collect();
+    let parameters: HashMap<&str, &Value> = cdi3_entries(cdi, "Parameters").collect();
+    let parts: HashMap<&str, &Value> = cdi3_entries(cdi, "Parts").collect();
+    for parameter in &mut info.parameters {
+        let Some(entry) = parameters.get(parameter.id.as_str()) else {
+            continue;
+        };
+        parameter.name = text(entry, "Name");
+        parameter.group = entry.get("GroupId").and_then(Value::as_str).and_then(|group_id| {
+            groups
+                .get(group_id)
+                .and_then(|group| text(group, "Name"))
+                .or_else(|| (!group_id.is_empty()).then(|| group_id.to_string()))
+        });
+    }
+    for part in &mut info.parts {
+        if let Some(entry) = parts.get(part.id.as_str()) {
+            part.name = text(entry, "Name");
+        }
+    }
+}
+
+pub(crate) fn read_moc3_info(path: &Path) -> Result<Moc3Info, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read moc3: {}", e))?;
+    let mut info = moc3::parse_moc3(&data)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let moc_file = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+    let moc_stem = strip_suffix_ignore_case(&moc_file, ".moc3").unwrap_or(&moc_file);
+    let dir_files: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    let moc_stems: Vec<&str> = dir_files
+        .iter()
+        .filter_map(|f| strip_suffix_ignore_case(f, ".moc3"))
+        .collect();
+    if let Some(cdi) = find_companion(&dir_files, ".cdi3.json", moc_stem, &moc_stems)
+        .and_then(|cdi| read_cdi3(&dir.join(cdi)))
+    {
+        apply_display_info(&mut info, &cdi);
+    }
+    Ok(info)
+}
+
+pub(crate) fn model_parameter_ids(dir: &Path, moc_file: &str, display_info: Option<&str>) -> Vec<String> {
+    let ids = display_info
+        .map(|cdi| cdi3_parameter_ids(&dir.join(cdi)))
+        .unwrap_or_default();
+    if !ids.is_empty() {
+        return ids;
+    }
+    moc3::read_parameter_ids(&dir.join(moc_file)).unwrap_or_default()
+}
+
+pub(crate) fn parameter_groups(parameter_ids: &[String]) -> Vec<Value> {
+    let mut groups = Vec::new();
+    for (name, targets) in [("EyeBlink", EYE_BLINK_PARAMETERS), ("LipSync", LIP_SYNC_PARAMETERS)] {
+        let ids: Vec<&String> = parameter_ids
+            .iter()
+            .filter(|id| targets.contains(&normalize_parameter_id(id).as_str()))
+            .collect();
+        if !ids.is_empty() {
+            groups.push(json!({
+                "Target": "Parameter",
+                "Name": name,
+                "Ids": ids
+            }));
+        }
+    }
+    groups
+}
+
+fn texture_index(name: &str) -> u32 {
+    let digits: String = name.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse().unwrap_or(u32::MAX)
+}
+
+fn file_name(rel_path: &str) -> &str {
+    rel_path.rsplit('/').next().unwrap_or(rel_path)
+}
+
+pub(crate) fn list_model_files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_file() {
+            files.push(name);
+        } else if path.is_dir()
+            && let Ok(sub_entries) = fs::read_dir(&path)
+        {
+            for sub_entry in sub_entries.filter_map(|e| e.ok()) {
+                if sub_entry.path().is_file() {
+                    files.push(format!("{}/{}", name, sub_entry.file_name().to_string_lossy()));
+                }
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+pub(crate) fn generate_cubism2_model_json(dir: &Path, moc_file: &str, moc_stem: &str) -> Value {
+    let files = list_model_files(dir);
+    let texture_dir_prefix = format!("{}.", moc_stem.to_lowercase());
+    let mut textures: Vec<&String> = files
+        .iter()
+        .filter(|f| is_live2d_texture_name(file_name(f)))
+        .collect();
+    let is_own_texture = |f: &String| f.contains('/') && f.to_lowercase().starts_with(&texture_dir_prefix);
+    if textures.iter().any(|f| is_own_texture(f)) {
+        textures.retain(|f| is_own_texture(f));
+    }
+    textures.sort_by_key(|f| texture_index(file_name(f)));
+    let find_file = |name: &str| {
+        files.iter().find(|f| {
+            let lower = file_name(f).to_lowercase();
+            lower == name || lower.ends_with(&format!(".{}", name))
+        })
+    };
+    let expressions: Vec<Value> = files
+        .iter()
+        .filter_map(|f| {
+            let name = strip_suffix_ignore_case(file_name(f), ".exp.json")?;
+            Some(json!({
+                "name": name,
+                "file": f
+            }))
+        })
+        .collect();
+    let mut motions: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for file in &files {
+        if let Some(stem) = strip_suffix_ignore_case(file_name(file), ".mtn") {
+            motions
+                .entry(motion_group(stem))
+                .or_default()
+                .push(json!({ "file": file }));
+        }
+    }
+
+    let mut model = Map::new();
+    model.insert("version".to_string(), json!("Sample 1.0.0"));
+    model.insert("model".to_string(), json!(moc_file));
+    model.insert("textures".to_string(), json!(textures));
+    if let Some(physics) = find_file("physics.json") {
+        model.insert("physics".to_string(), json!(physics));
+    }
+    if let Some(pose) = find_file("pose.json") {
+        model.insert("pose".to_string(), json!(pose));
+    }
+    if !expressions.is_empty() {
+        model.insert("expressions".to_string(), Value::Array(expressions));
+    }
+    if !motions.is_empty() {
+        model.insert("motions".to_string(), json!(motions));
+    }
+    Value::Object(model)
+}
+
+fn write_model_json(dir: &Path, file_name: &str, model: &Value, in_place: bool) -> Result<PathBuf, String> {
+    if !in_place {
+        return overlay::write_json(dir, file_name, model);
+    }
+    let path = dir.join(file_name);
+    let data = serde_json::to_vec_pretty(model).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+pub(crate) fn write_cubism2_model_json(
+    dir: &Path,
+    moc_file: &str,
+    moc_stem: &str,
+    in_place: bool,
+) -> Result<PathBuf, String> {
+    let model = generate_cubism2_model_json(dir, moc_file, moc_stem);
+    write_model_json(dir, &format!("{}.json", moc_stem), &model, in_place)
+}
+
+fn relocate(dir: &Path, sub_dir: &str, file_name: &str) -> Option<String> {
+    let src_path = dir.join(file_name);
+    let dest_dir = dir.join(sub_dir);
+    let dest_path = dest_dir.join(file_name);
+    if src_path.exists() {
+        let _ = fs::create_dir_all(&dest_dir);
+        if let Err(e) = fs::rename(&src_path, &dest_path) {
+            if fs::copy(&src_path, &dest_path).is_ok() {
+                let _ = fs::remove_file(&src_path);
+            } else {
+                eprintln!("Failed to move {}: {}", file_name, e);
+            }
+        }
+    }
+    if dest_path.exists() {
+        Some(format!("{}/{}", sub_dir, file_name))
+    } else {
+        src_path.exists().then(|| file_name.to_string())
+    }
+}
+
+pub(crate) fn generate_model3_json(
+    dir: &Path,
+    moc_file: &str,
+    moc_stem: &str,
+    dir_files: &[String],
+    in_place: bool,
+) -> Value {
+    let mut textures = Vec::new();
+    let mut expressions = Vec::new();
+    let mut motions: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    let moc_stems: Vec<&str> = dir_files
+        .iter()
+        .filter_map(|f| strip_suffix_ignore_case(f, ".moc3"))
+        .collect();
+    let physics = find_companion(dir_files, ".physics3.json", moc_stem, &moc_stems);
+    let display_info = find_companion(dir_files, ".cdi3.json", moc_stem, &moc_stems);
+    let userdata = find_companion(dir_files, ".userdata3.json", moc_stem, &moc_stems);
+    let pose = find_companion(dir_files, ".pose3.json", moc_stem, &moc_stems);
+    let locate = |sub_dir: &str, file_name: &str| {
+        if in_place {
+            relocate(dir, sub_dir, file_name)
+        } else {
+            Some(file_name.to_string())
+        }
+    };
+    for file_name in dir_files {
+        if is_live2d_texture_name(file_name) {
+            textures.extend(locate("textures", file_name));
+        } else if let Some(name) = strip_suffix_ignore_case(file_name, ".exp3.json") {
+            expressions.push(json!({
+                "Name": name,
+                "File": file_name
+            }));
+        } else if let Some(stem) = strip_suffix_ignore_case(file_name, ".motion3.json")
+            && let Some(file) = locate("motions", file_name)
+        {
+            let group = match motion_group(stem) {
+                g if g.eq_ignore_ascii_case("idle") => "Idle".to_string(),
+                g => g.to_string(),
+            };
+            motions.entry(group).or_default().push(json!({ "File": file }));
+        }
+    }
+    textures.sort_by_key(|f| texture_index(file_name(f)));
+
+    let mut file_references = Map::new();
+    file_references.insert("Moc".to_string(), json!(moc_file));
+    file_references.insert("Textures".to_string(), json!(textures));
+    for (key, file) in [
+        ("Physics", physics),
+        ("DisplayInfo", display_info),
+        ("UserData", userdata),
+        ("Pose", pose),
+    ] {
+        if let Some(file) = file {
+            file_references.insert(key.to_string(), json!(file));
+        }
+    }
+    if !expressions.is_empty() {
+        file_references.insert("Expressions".to_string(), Value::Array(expressions));
+    }
+    if !motions.is_empty() {
+        file_references.insert("Motions".to_string(), json!(motions));
+    }
+    let mut model = Map::new();
+    model.insert("Version".to_string(), json!(3));
+    model.insert("FileReferences".to_string(), Value::Object(file_references));
+    let parameter_ids = model_parameter_ids(dir, moc_file, display_info.map(String::as_str));
+    let groups = parameter_groups(&parameter_ids);
+    if !groups.is_empty() {
+        model.insert("Groups".to_string(), Value::Array(groups));
+    }
+    Value::Object(model)
+}
+
+pub(crate) fn write_model3_json(
+    dir: &Path,
+    moc_file: &str,
+    moc_stem: &str,
+    dir_files: &[String],
+    in_place: bool,
+) -> Result<PathBuf, String> {
+    let model = generate_model3_json(dir, moc_file, moc_stem, dir_files, in_place);
+    write_model_json(dir, &format!("{}.model3.json", moc_stem), &model, in_place)
+}
@@ -0,0 +1,210 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+pub(crate) const LATEST_SUPPORTED_MOC_VERSION: u8 = 5;
+
+const HEADER_LEN: usize = 64;
+const ID_LEN: usize = 64;
+const SECTION_COUNT_INFO: usize = 0;
+const SECTION_CANVAS_INFO: usize = 1;
+const SECTION_PART_IDS: usize = 3;
+const SECTION_PARAMETER_IDS: usize = 50;
+const SECTION_PARAMETER_MAX_VALUES: usize = 51;
+const SECTION_PARAMETER_MIN_VALUES: usize = 52;
+const SECTION_PARAMETER_DEFAULT_VALUES: usize = 53;
+const COUNT_PARTS: usize = 0;
+const COUNT_DEFORMERS: usize = 1;
+const COUNT_ART_MESHES: usize = 4;
+const COUNT_PARAMETERS: usize = 5;
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub(crate) struct Moc3Canvas {
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    #[serde(rename = "originX")]
+    pub(crate) origin_x: f32,
+    #[serde(rename = "originY")]
+    pub(crate) origin_y: f32,
+    #[serde(rename = "pixelsPerUnit")]
+    pub(crate) pixels_per_unit: f32,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub(crate) struct Moc3Parameter {
+    pub(crate) id: String,
+    pub(crate) name: Option<String>,
+    pub(crate) group: Option<String>,
+    pub(crate) min: f32,
+    pub(crate) default: f32,
+    pub(crate) max: f32,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub(crate) struct Moc3Part {
+    pub(crate) id: String,
+    pub(crate) name: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub(crate) struct Moc3Info {
+    #[serde(rename = "mocVersion")]
+    pub(crate) moc_version: u8,
+    pub(crate) version: String,
+    pub(crate) supported: bool,
+    pub(crate) canvas: Moc3Canvas,
+    pub(crate) parameters: Vec<Moc3Parameter>,
+    pub(crate) parts: Vec<Moc3Part>,
+    #[serde(rename = "deformerCount")]
+    pub(crate) deformer_count: usize,
+    #[serde(rename = "drawableCount")]
+    pub(crate) drawable_count: usize,
+}
+
+struct Moc3Reader<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Moc3Reader<'a> {
+    fn new(data: &'a [u8]) -> Result<Self, String> {
+        if data.len() < HEADER_LEN || &data[..4] != b"MOC3" {
+            return Err("Not a moc3 file".to_string());
+        }
+        Ok(Self {
+            data,
+            big_endian: data[5] != 0,
+        })
+    }
+
+    fn decode_u32(&self, bytes: [u8; 4]) -> u32 {
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    fn read_u32(&self, offset: usize) -> Result<u32, String> {
+        let bytes: [u8; 4] = self
+            .data
+            .get(offset..offset + 4)
+            .and_then(|b| b.try_into().ok())
+            .ok_or("Unexpected end of moc3 data")?;
+        Ok(self.decode_u32(bytes))
+    }
+
+    fn read_f32(&self, offset: usize) -> Result<f32, String> {
+        self.read_u32(offset).map(f32::from_bits)
+    }
+
+    fn section(&self, index: usize) -> Result<usize, String> {
+        Ok(self.read_u32(HEADER_LEN + index * 4)? as usize)
+    }
+
+    fn count(&self, index: usize) -> Result<usize, String> {
+        let table = self.section(SECTION_COUNT_INFO)?;
+        Ok(self.read_u32(table + index * 4)? as usize)
+    }
+
+    fn table(&self, section: usize, count: usize, item_len: usize) -> Result<&'a [u8], String> {
+        let start = self.section(section)?;
+        let end = count
+            .checked_mul(item_len)
+            .and_then(|len| start.checked_add(len))
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Section exceeds moc3 data")?;
+        Ok(&self.data[start..end])
+    }
+
+    fn read_ids(&self, section: usize, count: usize) -> Result<Vec<String>, String> {
+        Ok(self
+            .table(section, count, ID_LEN)?
+            .chunks_exact(ID_LEN)
+            .map(|id| {
+                let len = id.iter().position(|b| *b == 0).unwrap_or(ID_LEN);
+                String::from_utf8_lossy(&id[..len]).into_owned()
+            })
+            .collect())
+    }
+
+    fn read_floats(&self, section: usize, count: usize) -> Result<Vec<f32>, String> {
+        Ok(self
+            .table(section, count, 4)?
+            .chunks_exact(4)
+            .map(|b| f32::from_bits(self.decode_u32([b[0], b[1], b[2], b[3]])))
+            .collect())
+    }
+}
+
+pub(crate) fn runtime_version(moc_version: u8) -> String {
+    match moc_version {
+        1 => "3.0".to_string(),
+        2 => "3.3".to_string(),
+        3 => "4.0".to_string(),
+        4 => "4.2".to_string(),
+        5 => "5.0".to_string(),
+        v => format!("moc3 v{}", v),
+    }
+}
+
+pub(crate) fn read_moc_version(path: &Path) -> Option<u8> {
+    let mut header = [0u8; 5];
+    fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)).ok()?;
+    (&header[..4] == b"MOC3").then_some(header[4])
+}
+
+pub(crate) fn parse_parameter_ids(data: &[u8]) -> Result<Vec<String>, String> {
+    let reader = Moc3Reader::new(data)?;
+    reader.read_ids(SECTION_PARAMETER_IDS, reader.count(COUNT_PARAMETERS)?)
+}
+
+pub(crate) fn read_parameter_ids(path: &Path) -> Result<Vec<String>, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read moc3: {}", e))?;
+    parse_parameter_ids(&data)
+}
+
+pub(crate) fn parse_moc3(data: &[u8]) -> Result<Moc3Info, String> {
+    let reader = Moc3Reader::new(data)?;
+    let moc_version = data[4];
+    let canvas_info = reader.section(SECTION_CANVAS_INFO)?;
+    let canvas = Moc3Canvas {
+        pixels_per_unit: reader.read_f32(canvas_info)?,
+        origin_x: reader.read_f32(canvas_info + 4)?,
+        origin_y: reader.read_f32(canvas_info + 8)?,
+        width: reader.read_f32(canvas_info + 12)?,
+        height: reader.read_f32(canvas_info + 16)?,
+    };
+    let parameter_count = reader.count(COUNT_PARAMETERS)?;
+    let ids = reader.read_ids(SECTION_PARAMETER_IDS, parameter_count)?;
+    let max_values = reader.read_floats(SECTION_PARAMETER_MAX_VALUES, parameter_count)?;
+    let min_values = reader.read_floats(SECTION_PARAMETER_MIN_VALUES, parameter_count)?;
+    let default_values = reader.read_floats(SECTION_PARAMETER_DEFAULT_VALUES, parameter_count)?;
+    let parameters = ids
+        .into_iter()
+        .enumerate()
+        .map(|(i, id)| Moc3Parameter {
+            id,
+            name: None,
+            group: None,
+            min: min_values[i],
+            default: default_values[i],
+            max: max_values[i],
+        })
+        .collect();
+    let parts = reader
+        .read_ids(SECTION_PART_IDS, reader.count(COUNT_PARTS)?)?
+        .into_iter()
+        .map(|id| Moc3Part { id, name: None })
+        .collect();
+    Ok(Moc3Info {
+        moc_version,
+        version: runtime_version(moc_version),
+        supported: moc_version <= LATEST_SUPPORTED_MOC_VERSION,
+        canvas,
+        parameters,
+        parts,
+        deformer_count: reader.count(COUNT_DEFORMERS)?,
+        drawable_count: reader.count(COUNT_ART_MESHES)?,
+    })
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::{ImageFormat, RgbaImage};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub(crate) fn new() -> Self {
+        StableHasher(FNV_OFFSET)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+pub(crate) fn overlay_root() -> PathBuf {
+    std::env::temp_dir().join("spive2d").join("overlay")
+}
+
+pub(crate) fn overlay_dir(source_dir: &Path) -> PathBuf {
+    let source = fs::canonicalize(source_dir).unwrap_or_else(|_| source_dir.to_path_buf());
+    let mut hasher = StableHasher::new();
+    hasher.write(source.to_string_lossy().as_bytes());
+    overlay_root().join(format!("{:016x}", hasher.finish()))
+}
+
+pub(crate) fn find(source_dir: &Path, rel_path: &str) -> Option<PathBuf> {
+    let path = overlay_dir(source_dir).join(rel_path);
+    path.is_file().then_some(path)
+}
+
+pub(crate) fn find_newer(source_dir: &Path, rel_path: &str) -> Option<PathBuf> {
+    let path = find(source_dir, rel_path)?;
+    let generated = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let source = fs::metadata(source_dir.join(rel_path)).and_then(|m| m.modified()).ok()?;
+    (source <= generated).then_some(path)
+}
+
+pub(crate) fn write(source_dir: &Path, rel_path: &str, data: &[u8]) -> Result<PathBuf, String> {
+    let path = overlay_dir(source_dir).join(rel_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create overlay directory: {}", e))?;
+    }
+    fs::write(&path, data).map_err(|e| format!("Failed to write overlay file: {}", e))?;
+    Ok(path)
+}
+
+pub(crate) fn remove(source_dir: &Path, rel_path: &str) -> Result<bool, String> {
+    let Some(path) = find(source_dir, rel_path) else {
+        return Ok(false);
+    };
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove overlay file: {}", e))?;
+    Ok(true)
+}
+
+pub(crate) fn write_json(source_dir: &Path, rel_path: &str, value: &serde_json::Value) -> Result<PathBuf, String> {
+    let data = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    write(source_dir, rel_path, &data)
+}
+
+pub(crate) fn write_image(source_dir: &Path, rel_path: &str, image: &RgbaImage) -> Result<PathBuf, String> {
+    let mut data = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode {}: {}", rel_path, e))?;
+    write(source_dir, rel_path, &data)
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const PACKAGE_EXTENSIONS: &[&str] = &["apk", "xapk", "apks", "apkm", "obb", "ipa"];
+const MAX_NESTING_DEPTH: usize = 3;
+
+pub(crate) fn is_package_ext(ext: &str) -> bool {
+    PACKAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+}
+
+fn is_skipped_entry(name: &str) -> bool {
+    let name_lower = name.to_lowercase();
+    for prefix in &["meta-inf/", "lib/", "res/", "kotlin/", "okhttp3/", "org/", "com/"] {
+        if name_lower.starts_with(prefix) {
+            return true;
+        }
+    }
+    for ext in &[".dex", ".so", ".arsc", ".dylib", ".car", ".nib", ".storyboardc", ".mobileprovision"] {
+        if name_lower.ends_with(ext) {
+            return true;
+        }
+    }
+    name_lower.contains("/frameworks/") || name_lower.contains("/_codesignature/")
+}
+
+pub(crate) fn extract_package(path: &Path, out_dir: &Path) -> Result<(), String> {
+    extract_package_at_depth(path, out_dir, 0)
+}
+
+fn extract_package_at_depth(path: &Path, out_dir: &Path, depth: usize) -> Result<(), String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open package: {}", e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read package: {}", e))?;
+    let mut nested_packages = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read package entry: {}", e))?;
+        if entry.is_dir() || is_skipped_entry(entry.name()) {
+            continue;
+        }
+        let rel_path = match entry.enclosed_name() {
+            Some(p) => p,
+            None => continue,
+        };
+        let is_nested = rel_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(is_package_ext)
+            .unwrap_or(false);
+        if is_nested && depth >= MAX_NESTING_DEPTH {
+            continue;
+        }
+        let dest_path = out_dir.join(&rel_path);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = fs::File::create(&dest_path).map_err(|e| e.to_string())?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to extract {}: {}", rel_path.display(), e))?;
+        if is_nested {
+            nested_packages.push(dest_path);
+        }
+    }
+    for nested_path in nested_packages {
+        let stem = nested_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "package".to_string());
+        let nested_out_dir = nested_path.with_file_name(stem);
+        let result = extract_package_at_depth(&nested_path, &nested_out_dir, depth + 1);
+        let _ = fs::remove_file(&nested_path);
+        if let Err(e) = result {
+            eprintln!("Failed to extract nested package {:?}: {}", nested_path, e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+
+use crate::atlas::{Atlas, AtlasPage, AtlasRegion};
+use crate::overlay;
+use crate::spine::render::file_stem;
+
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct PackOptions {
+    pub(crate) max_size: u32,
+    pub(crate) padding: u32,
+    pub(crate) rotation: bool,
+    pub(crate) power_of_two: bool,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 2048,
+            padding: 2,
+            rotation: false,
+            power_of_two: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Placement {
+    pub(crate) page: usize,
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) rotated: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Packing {
+    pub(crate) page_sizes: Vec<(u32, u32)>,
+    pub(crate) placements: Vec<Placement>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    width: u32,
+}
+
+fn floor_power_of_two(value: u32) -> u32 {
+    if value == 0 { 0 } else { 1 << value.ilog2() }
+}
+
+pub(crate) fn pack(sizes: &[(u32, u32)], options: &PackOptions) -> Result<Packing, String> {
+    let padding = options.padding;
+    let max_size = if options.power_of_two {
+        floor_power_of_two(options.max_size)
+    } else {
+        options.max_size
+    };
+    let fits = |width: u32, height: u32| width + padding * 2 <= max_size && height + padding * 2 <= max_size;
+    let oriented: Vec<(u32, u32, bool)> = sizes
+        .iter()
+        .map(|&(width, height)| {
+            let rotate = options.rotation && fits(height, width) && (height > width || !fits(width, height));
+            if rotate { (height, width, true) } else { (width, height, false) }
+        })
+        .collect();
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|i| std::cmp::Reverse((oriented[*i].1, oriented[*i].0)));
+    let mut placements = vec![
+        Placement {
+            page: 0,
+            x: 0,
+            y: 0,
+            rotated: false,
+        };
+        sizes.len()
+    ];
+    let mut pages: Vec<Vec<Shelf>> = Vec::new();
+    let mut page_sizes: Vec<(u32, u32)> = Vec::new();
+    for index in order {
+        let (width, height, rotated) = oriented[index];
+        if !fits(width, height) {
+            return Err(format!("A {}x{} image does not fit into a {} page", width, height, max_size));
+        }
+        let mut placed = None;
+        for (page, shelves) in pages.iter_mut().enumerate() {
+            if let Some(shelf) = shelves
+                .iter_mut()
+                .find(|s| s.height >= height + padding && s.width + width + padding * 2 <= max_size)
+            {
+                placed = Some((page, shelf.width + padding, shelf.y + padding));
+                shelf.width += width + padding;
+                break;
+            }
+            let top = shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+            if top + height + padding * 2 <= max_size {
+                shelves.push(Shelf {
+                    y: top,
+                    height: height + padding,
+                    width: width + padding,
+                });
+                placed = Some((page, padding, top + padding));
+                break;
+            }
+        }
+        let (page, x, y) = placed.unwrap_or_else(|| {
+            pages.push(vec![Shelf {
+                y: 0,
+                height: height + padding,
+                width: width + padding,
+            }]);
+            page_sizes.push((0, 0));
+            (pages.len() - 1, padding, padding)
+        });
+        let page_size = &mut page_sizes[page];
+        page_size.0 = page_size.0.max(x + width + padding);
+        page_size.1 = page_size.1.max(y + height + padding);
+        placements[index] = Placement { page, x, y, rotated };
+    }
+    if options.power_of_two {
+        for (width, height) in &mut page_sizes {
+            *width = width.next_power_of_two();
+            *height = height.next_power_of_two();
+        }
+    }
+    Ok(Packing {
+        page_sizes,
+        placements,
+    })
+}
+
+fn pack_regions(
+    entries: &[(AtlasRegion, RgbaImage)],
+    page_name: impl Fn(usize) -> String,
+    options: &PackOptions,
+) -> Result<(Atlas, Vec<RgbaImage>), String> {
+    let sizes: Vec<(u32, u32)> = entries.iter().map(|(_, image)| image.dimensions()).collect();
+    let Packing {
+        page_sizes,
+        placements,
+    } = pack(&sizes, options)?;
+    let mut pages: Vec<AtlasPage> = page_sizes
+        .iter()
+        .enumerate()
+        .map(|(i, (width, height))| AtlasPage {
+            name: page_name(i),
+            width: *width,
+            height: *height,
+            ..Default::default()
+        })
+        .collect();
+    let mut canvases: Vec<RgbaImage> = page_sizes
+        .iter()
+        .map(|(width, height)| RgbaImage::new(*width, *height))
+        .collect();
+    for ((template, image), placement) in entries.iter().zip(&placements) {
+        let (width, height) = image.dimensions();
+        let canvas = &mut canvases[placement.page];
+        let (x, y) = (placement.x as i64, placement.y as i64);
+        if placement.rotated {
+            image::imageops::replace(canvas, &image::imageops::rotate270(image), x, y);
+        } else {
+            image::imageops::replace(canvas, image, x, y);
+        }
+        pages[placement.page].regions.push(AtlasRegion {
+            x: placement.x,
+            y: placement.y,
+            width,
+            height,
+            degrees: if placement.rotated { 90 } else { 0 },
+            ..template.clone()
+        });
+    }
+    for page in &mut pages {
+        page.region_count = page.regions.len();
+    }
+    Ok((Atlas { pages }, canvases))
+}
+
+pub(crate) fn pack_images(
+    images: &[(String, RgbaImage)],
+    page_name: impl Fn(usize) -> String,
+    options: &PackOptions,
+) -> Result<(Atlas, Vec<RgbaImage>), String> {
+    let entries: Vec<(AtlasRegion, RgbaImage)> = images
+        .iter()
+        .map(|(name, image)| {
+            let region = AtlasRegion {
+                name: name.clone(),
+                orig_width: image.width(),
+                orig_height: image.height(),
+                index: -1,
+                ..Default::default()
+            };
+            (region, image.clone())
+        })
+        .collect();
+    pack_regions(&entries, page_name, options)
+}
+
+pub(crate) fn page_names(atlas_file: &str) -> impl Fn(usize) -> String {
+    let file_name = atlas_file.rsplit('/').next().unwrap_or(atlas_file);
+    let stem = match file_name.to_lowercase().rfind(".atlas") {
+        Some(idx) => file_name[..idx].to_string(),
+        None => file_name.to_string(),
+    };
+    move |i: usize| match i {
+        0 => format!("{}.png", stem),
+        i => format!("{}_{}.png", stem, i + 1),
+    }
+}
+
+pub(crate) fn write_overlay(
+    dir: &Path,
+    atlas_file: &str,
+    atlas: &Atlas,
+    pages: &[RgbaImage],
+) -> Result<PathBuf, String> {
+    let atlas_dir = match atlas_file.rfind('/') {
+        Some(idx) => &atlas_file[..=idx],
+        None => "",
+    };
+    for (page, canvas) in atlas.pages.iter().zip(pages) {
+        overlay::write_image(dir, &format!("{}{}", atlas_dir, page.name), canvas)?;
+    }
+    overlay::write(dir, atlas_file, atlas.to_text().as_bytes())
+}
+
+pub(crate) fn remove_overlay(dir: &Path, atlas_file: &str) -> Result<bool, String> {
+    let Some(path) = overlay::find(dir, atlas_file) else {
+        return Ok(false);
+    };
+    let atlas_dir = match atlas_file.rfind('/') {
+        Some(idx) => &atlas_file[..=idx],
+        None => "",
+    };
+    if let Ok(atlas) = Atlas::read(&path) {
+        for page in &atlas.pages {
+            overlay::remove(dir, &format!("{}{}", atlas_dir, page.name))?;
+        }
+    }
+    overlay::remove(dir, atlas_file)
+}
+
+pub(crate) fn packed_size(region: &AtlasRegion) -> (u32, u32) {
+    match region.degrees {
+        90 | 270 => (region.height, region.width),
+        _ => (region.width, region.height),
+    }
+}
+
+fn region_image(page: &AtlasPage, image: &RgbaImage, region: &AtlasRegion) -> RgbaImage {
+    let (width, height) = packed_size(region);
+    let scale_x = if page.width > 0 { image.width() as f32 / page.width as f32 } else { 1.0 };
+    let scale_y = if page.height > 0 { image.height() as f32 / page.height as f32 } else { 1.0 };
+    let x = ((region.x as f32 * scale_x).round() as u32).min(image.width());
+    let y = ((region.y as f32 * scale_y).round() as u32).min(image.height());
+    let crop_w = ((width as f32 * scale_x).round() as u32).min(image.width() - x);
+    let crop_h = ((height as f32 * scale_y).round() as u32).min(image.height() - y);
+    let mut packed = image::imageops::crop_imm(image, x, y, crop_w, crop_h).to_image();
+    if packed.dimensions() != (width, height) && width > 0 && height > 0 {
+        packed = image::imageops::resize(&packed, width, height, image::imageops::FilterType::Triangle);
+    }
+    match region.degrees {
+        90 => image::imageops::rotate90(&packed),
+        180 => image::imageops::rotate180(&packed),
+        270 => image::imageops::rotate270(&packed),
+        _ => packed,
+    }
+}
+
+pub(crate) fn unpack_region(page: &AtlasPage, image: &RgbaImage, region: &AtlasRegion) -> RgbaImage {
+    let upright = region_image(page, image, region);
+    let orig_width = region.orig_width.max(region.width).max(1);
+    let orig_height = region.orig_height.max(region.height).max(1);
+    let mut canvas = RgbaImage::new(orig_width, orig_height);
+    let top = orig_height as i64 - region.offset_y as i64 - region.height as i64;
+    image::imageops::replace(&mut canvas, &upright, region.offset_x as i64, top);
+    canvas
+}
+
+fn region_file(region: &AtlasRegion) -> PathBuf {
+    let mut path: PathBuf = region
+        .name
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .map(file_stem)
+        .collect();
+    let stem = path.file_name().and_then(|n| n.to_str()).unwrap_or("region").to_string();
+    let name = if region.index >= 0 {
+        format!("{}_{}.png", stem, region.index)
+    } else {
+        format!("{}.png", stem)
+    };
+    path.set_file_name(name);
+    path
+}
+
+pub(crate) fn load_page(atlas_path: &Path, page: &AtlasPage) -> Result<RgbaImage, String> {
+    let path = atlas_path.parent().unwrap_or(Path::new(".")).join(&page.name);
+    Ok(image::open(&path)
+        .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?
+        .to_rgba8())
+}
+
+pub(crate) fn unpack_atlas(atlas_path: &Path, output_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let atlas = Atlas::read(atlas_path)?;
+    let mut paths = Vec::new();
+    for page in &atlas.pages {
+        let image = load_page(atlas_path, page)?;
+        for region in &page.regions {
+            let path = output_dir.join(region_file(region));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            unpack_region(page, &image, region)
+                .save(&path)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+pub(crate) fn repack_atlas(atlas_path: &Path, options: &PackOptions) -> Result<(Atlas, Vec<RgbaImage>), String> {
+    let source = Atlas::read(atlas_path)?;
+    let mut entries = Vec::new();
+    for page in &source.pages {
+        let image = load_page(atlas_path, page)?;
+        for region in &page.regions {
+            let template = AtlasRegion {
+                x: 0,
+                y: 0,
+                degrees: 0,
+                ..region.clone()
+            };
+            entries.push((template, region_image(page, &image, region)));
+        }
+    }
+    let atlas_file = atlas_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let (mut atlas, pages) = pack_regions(&entries, page_names(atlas_file), options)?;
+    if let Some(first) = source.pages.first() {
+        for page in &mut atlas.pages {
+            page.format.clone_from(&first.format);
+            page.min_filter.clone_from(&first.min_filter);
+            page.mag_filter.clone_from(&first.mag_filter);
+            page.repeat.clone_from(&first.repeat);
+            page.pma = first.pma;
+            page.scale = first.scale;
+        }
+    }
+    Ok((atlas, pages))
+}
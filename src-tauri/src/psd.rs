@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::Path;
+
+use image::RgbaImage;
+
+const CHANNEL_IDS: [i16; 4] = [-1, 0, 1, 2];
+const FLAG_HIDDEN: u8 = 0x02;
+
+pub(crate) struct PsdLayer {
+    pub(crate) name: String,
+    pub(crate) image: RgbaImage,
+    pub(crate) left: i32,
+    pub(crate) top: i32,
+    pub(crate) visible: bool,
+}
+
+fn pad(buf: &mut Vec<u8>, multiple: usize) {
+    while !buf.len().is_multiple_of(multiple) {
+        buf.push(0);
+    }
+}
+
+fn channel(image: &RgbaImage, id: i16) -> Vec<u8> {
+    let index = if id < 0 { 3 } else { id as usize };
+    image.pixels().map(|pixel| pixel[index]).collect()
+}
+
+fn pascal_name(name: &str) -> Vec<u8> {
+    let bytes: Vec<u8> = name
+        .chars()
+        .map(|c| if c.is_ascii() && !c.is_ascii_control() { c as u8 } else { b'_' })
+        .take(255)
+        .collect();
+    let mut out = vec![bytes.len() as u8];
+    out.extend(bytes);
+    pad(&mut out, 4);
+    out
+}
+
+fn unicode_name(name: &str) -> Vec<u8> {
+    let units: Vec<u16> = name.encode_utf16().collect();
+    let mut data = (units.len() as u32).to_be_bytes().to_vec();
+    for unit in units {
+        data.extend_from_slice(&unit.to_be_bytes());
+    }
+    pad(&mut data, 4);
+    let mut out = b"8BIMluni".to_vec();
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend(data);
+    out
+}
+
+fn layer_record(layer: &PsdLayer) -> Vec<u8> {
+    let (width, height) = layer.image.dimensions();
+    let mut out = Vec::new();
+    for edge in [layer.top, layer.left, layer.top + height as i32, layer.left + width as i32] {
+        out.extend_from_slice(&edge.to_be_bytes());
+    }
+    out.extend_from_slice(&(CHANNEL_IDS.len() as u16).to_be_bytes());
+    for id in CHANNEL_IDS {
+        out.extend_from_slice(&id.to_be_bytes());
+        out.extend_from_slice(&(2 + width * height).to_be_bytes());
+    }
+    out.extend_from_slice(b"8BIMnorm");
+    out.extend_from_slice(&[255, 0, if layer.visible { 0 } else { FLAG_HIDDEN }, 0]);
+    let mut extra = vec![0; 8];
+    extra.extend(pascal_name(&layer.name));
+    extra.extend(unicode_name(&layer.name));
+    out.extend_from_slice(&(extra.len() as u32).to_be_bytes());
+    out.extend(extra);
+    out
+}
+
+fn layer_info(layers: &[PsdLayer]) -> Vec<u8> {
+    let mut info = Vec::new();
+    info.extend_from_slice(&(-(layers.len() as i16)).to_be_bytes());
+    for layer in layers {
+        info.extend(layer_record(layer));
+    }
+    for layer in layers {
+        for id in CHANNEL_IDS {
+            info.extend_from_slice(&0u16.to_be_bytes());
+            info.extend(channel(&layer.image, id));
+        }
+    }
+    pad(&mut info, 2);
+    let mut out = (info.len() as u32).to_be_bytes().to_vec();
+    out.extend(info);
+    out
+}
+
+pub(crate) fn write(path: &Path, composite: &RgbaImage, layers: &[PsdLayer]) -> Result<(), String> {
+    let (width, height) = composite.dimensions();
+    if width > 30000 || height > 30000 {
+        return Err(format!("Image is too large for PSD: {}x{}", width, height));
+    }
+    let mut out = b"8BPS".to_vec();
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&[0; 6]);
+    out.extend_from_slice(&4u16.to_be_bytes());
+    out.extend_from_slice(&height.to_be_bytes());
+    out.extend_from_slice(&width.to_be_bytes());
+    out.extend_from_slice(&8u16.to_be_bytes());
+    out.extend_from_slice(&3u16.to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes());
+    let mut layer_section = layer_info(layers);
+    layer_section.extend_from_slice(&0u32.to_be_bytes());
+    out.extend_from_slice(&(layer_section.len() as u32).to_be_bytes());
+    out.extend(layer_section);
+    out.extend_from_slice(&0u16.to_be_bytes());
+    for id in [0, 1, 2, -1] {
+        out.extend(channel(composite, id));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, out).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
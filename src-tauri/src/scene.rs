@@ -0,0 +1,648 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::atlas::{Atlas, AtlasPage};
+use crate::live2d;
+use crate::moc3;
+use crate::overlay;
+use crate::spine::{self, SpineMetadata};
+
+pub(crate) const SCENE_SCHEMA_VERSION: u32 = 1;
+pub(crate) const DEFAULT_MERGE_LIMIT: usize = 20;
+pub(crate) const IMAGE_EXTENSIONS: &[&str] = &[".png", ".jpg", ".jpeg", ".webp"];
+
+pub(crate) fn is_image(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    IMAGE_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+pub(crate) fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SceneKind {
+    Spine,
+    Cubism3,
+    Cubism2,
+    LayeredSprite,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CompanionRole {
+    Behind,
+    Front,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub(crate) struct CompanionRule {
+    pub(crate) suffix: String,
+    pub(crate) role: CompanionRole,
+}
+
+pub(crate) fn default_companion_rules() -> Vec<CompanionRule> {
+    vec![
+        CompanionRule {
+            suffix: "_bg".to_string(),
+            role: CompanionRole::Behind,
+        },
+        CompanionRule {
+            suffix: "_fg".to_string(),
+            role: CompanionRole::Front,
+        },
+    ]
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ScanOptions {
+    pub(crate) include_metadata: bool,
+    pub(crate) fix_in_place: bool,
+    pub(crate) read_only: bool,
+    pub(crate) companions: Option<Vec<CompanionRule>>,
+    pub(crate) merge_limit: Option<usize>,
+    pub(crate) max_texture_size: Option<u32>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct Companion {
+    pub(crate) file: String,
+    pub(crate) role: CompanionRole,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) code: String,
+    pub(crate) message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) file: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub(crate) struct SceneData {
+    #[serde(rename = "schemaVersion")]
+    pub(crate) schema_version: u32,
+    pub(crate) kind: SceneKind,
+    pub(crate) name: String,
+    #[serde(rename = "mainExt")]
+    pub(crate) main_ext: String,
+    #[serde(rename = "atlasExt")]
+    pub(crate) atlas_ext: String,
+    pub(crate) files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) companions: Vec<Companion>,
+    #[serde(rename = "isMerged")]
+    pub(crate) is_merged: bool,
+    pub(crate) version: Option<String>,
+    pub(crate) assets: Vec<String>,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    #[serde(rename = "atlasPages")]
+    pub(crate) atlas_pages: Vec<AtlasPage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata: Option<SpineMetadata>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) overlay: BTreeMap<String, String>,
+    #[serde(rename = "atlasBases", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) atlas_bases: BTreeMap<String, String>,
+    #[serde(rename = "looseImages", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) loose_images: BTreeMap<String, String>,
+}
+
+impl SceneData {
+    pub(crate) fn new(kind: SceneKind, name: String, main_ext: String, atlas_ext: String) -> Self {
+        Self {
+            schema_version: SCENE_SCHEMA_VERSION,
+            kind,
+            name,
+            main_ext,
+            atlas_ext,
+            files: Vec::new(),
+            companions: Vec::new(),
+            is_merged: false,
+            version: None,
+            assets: Vec::new(),
+            diagnostics: Vec::new(),
+            atlas_pages: Vec::new(),
+            metadata: None,
+            overlay: BTreeMap::new(),
+            atlas_bases: BTreeMap::new(),
+            loose_images: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn name_prefix(&self) -> &str {
+        match self.name.rfind('/') {
+            Some(idx) => &self.name[..=idx],
+            None => "",
+        }
+    }
+
+    pub(crate) fn scene_path(&self, rel_path: &str) -> String {
+        format!("{}{}", self.name_prefix(), rel_path)
+    }
+
+    pub(crate) fn resolve(&self, dir: &Path, rel_path: &str) -> PathBuf {
+        match self.overlay.get(rel_path) {
+            Some(path) => PathBuf::from(path),
+            None => dir.join(rel_path),
+        }
+    }
+
+    pub(crate) fn add_diagnostic(
+        &mut self,
+        severity: Severity,
+        code: &str,
+        message: String,
+        file: Option<String>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            code: code.to_string(),
+            message,
+            file,
+        });
+    }
+
+    fn push_asset(&mut self, rel_path: &str) -> Option<String> {
+        let asset_path = self.scene_path(rel_path);
+        if self.assets.contains(&asset_path) {
+            return None;
+        }
+        self.assets.push(asset_path.clone());
+        Some(asset_path)
+    }
+
+    pub(crate) fn add_overlay(&mut self, rel_path: &str, overlay_path: &Path) {
+        let key = self.scene_path(rel_path);
+        let value = overlay_path
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, "/");
+        self.overlay.insert(key, value);
+    }
+
+    pub(crate) fn add_asset(&mut self, dir: &Path, rel_path: &str) {
+        let rel_path = rel_path.replace('\\', "/");
+        if let Some(asset_path) = self.push_asset(&rel_path)
+            && !dir.join(&rel_path).is_file()
+        {
+            self.add_diagnostic(
+                Severity::Error,
+                "missingFile",
+                format!("Referenced file not found: {}", rel_path),
+                Some(asset_path),
+            );
+        }
+    }
+}
+
+fn read_json(dir: &Path, file_name: &str, scene: &mut SceneData) -> Option<serde_json::Value> {
+    let content = match fs::read(dir.join(file_name)) {
+        Ok(c) => c,
+        Err(e) => {
+            scene.add_diagnostic(
+                Severity::Error,
+                "unreadableFile",
+                format!("Failed to read {}: {}", file_name, e),
+                Some(scene.scene_path(file_name)),
+            );
+            return None;
+        }
+    };
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&content);
+    match serde_json::from_slice(content) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            scene.add_diagnostic(
+                Severity::Error,
+                "invalidJson",
+                format!("Failed to parse {}: {}", file_name, e),
+                Some(scene.scene_path(file_name)),
+            );
+            None
+        }
+    }
+}
+
+fn read_header(path: &Path, len: usize) -> Vec<u8> {
+    let mut header = vec![0u8; len];
+    let n = fs::File::open(path)
+        .and_then(|mut f| f.read(&mut header))
+        .unwrap_or(0);
+    header.truncate(n);
+    header
+}
+
+fn read_model_json(
+    dir: &Path,
+    model_file: &str,
+    moc_file: &str,
+    scene: &mut SceneData,
+) -> Option<serde_json::Value> {
+    if dir.join(model_file).is_file() {
+        scene.add_asset(dir, model_file);
+        return read_json(dir, model_file, scene);
+    }
+    scene.add_asset(dir, moc_file);
+    let Some(path) = overlay::find(dir, model_file) else {
+        scene.add_diagnostic(
+            Severity::Error,
+            "missingModelJson",
+            format!("{} not found", model_file),
+            Some(model_file.to_string()),
+        );
+        return None;
+    };
+    scene.add_overlay(model_file, &path);
+    scene.add_diagnostic(
+        Severity::Warning,
+        "generatedModelJson",
+        format!("{} not found, using a generated one", model_file),
+        Some(model_file.to_string()),
+    );
+    read_json(&overlay::overlay_dir(dir), model_file, scene)
+}
+
+pub(crate) fn resolve_cubism3(dir: &Path, moc_file: &str, moc_stem: &str, read_only: bool, scene: &mut SceneData) {
+    match moc3::read_moc_version(&dir.join(moc_file)) {
+        Some(moc_version) => {
+            scene.version = Some(moc3::runtime_version(moc_version));
+            if moc_version > moc3::LATEST_SUPPORTED_MOC_VERSION {
+                scene.add_diagnostic(
+                    Severity::Warning,
+                    "unsupportedMocVersion",
+                    format!("{} uses moc3 version {}, which the bundled runtime may not support", moc_file, moc_version),
+                    Some(scene.scene_path(moc_file)),
+                );
+            }
+        }
+        None => scene.add_diagnostic(
+            Severity::Warning,
+            "invalidMoc",
+            format!("{} does not have a valid moc3 header", moc_file),
+            Some(scene.scene_path(moc_file)),
+        ),
+    }
+    let model_file = format!("{}.model3.json", moc_stem);
+    let Some(mut model) = read_model_json(dir, &model_file, moc_file, scene) else {
+        return;
+    };
+    let Some(refs) = model.get_mut("FileReferences") else {
+        scene.add_diagnostic(
+            Severity::Error,
+            "invalidModelJson",
+            format!("{} has no FileReferences", model_file),
+            Some(scene.scene_path(&model_file)),
+        );
+        return;
+    };
+    let mut dir_files = None;
+    let mut repaired = false;
+    for value in model3_reference_values(refs) {
+        let Some(file) = value.as_str() else {
+            continue;
+        };
+        let file = file.to_string();
+        let Some(actual) = resolve_reference(dir, &file, &mut dir_files) else {
+            scene.add_asset(dir, &file);
+            continue;
+        };
+        if actual != file {
+            scene.add_diagnostic(
+                Severity::Warning,
+                "repairedReference",
+                format!("{} references {}, which was found as {}", model_file, file, actual),
+                Some(scene.scene_path(&actual)),
+            );
+            *value = serde_json::Value::String(actual.clone());
+            repaired = true;
+        }
+        scene.add_asset(dir, &actual);
+    }
+    if repaired && !read_only {
+        match overlay::write_json(dir, &model_file, &model) {
+            Ok(path) => scene.add_overlay(&model_file, &path),
+            Err(e) => scene.add_diagnostic(
+                Severity::Warning,
+                "overlayFailed",
+                format!("Failed to write a repaired {}: {}", model_file, e),
+                Some(scene.scene_path(&model_file)),
+            ),
+        }
+    }
+}
+
+fn model3_reference_values(refs: &mut serde_json::Value) -> Vec<&mut serde_json::Value> {
+    let mut values = Vec::new();
+    for (key, value) in refs.as_object_mut().into_iter().flatten() {
+        match key.as_str() {
+            "Moc" | "Physics" | "Pose" | "DisplayInfo" | "UserData" => values.push(value),
+            "Textures" => values.extend(value.as_array_mut().into_iter().flatten()),
+            "Expressions" => values.extend(
+                value
+                    .as_array_mut()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e.get_mut("File")),
+            ),
+            "Motions" => {
+                for motions in value.as_object_mut().into_iter().flat_map(|g| g.values_mut()) {
+                    for motion in motions.as_array_mut().into_iter().flatten() {
+                        for (key, file) in motion.as_object_mut().into_iter().flatten() {
+                            if key == "File" || key == "Sound" {
+                                values.push(file);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    values
+}
+
+fn resolve_reference(dir: &Path, rel_path: &str, dir_files: &mut Option<Vec<String>>) -> Option<String> {
+    if dir.join(rel_path).is_file() {
+        return Some(rel_path.to_string());
+    }
+    let rel_path = rel_path.replace('\\', "/");
+    if let Some(actual) = find_case_insensitive(dir, &rel_path) {
+        return Some(actual);
+    }
+    let file_name = normalize_name(rel_path.rsplit('/').next().unwrap_or(&rel_path));
+    let files = dir_files.get_or_insert_with(|| live2d::list_model_files(dir));
+    let mut matches = files
+        .iter()
+        .filter(|f| normalize_name(f.rsplit('/').next().unwrap_or(f)) == file_name);
+    match (matches.next(), matches.next()) {
+        (Some(actual), None) => Some(actual.clone()),
+        _ => None,
+    }
+}
+
+pub(crate) fn normalize_name(name: &str) -> String {
+    name.nfc().flat_map(char::to_lowercase).collect()
+}
+
+pub(crate) fn cubism2_model_json_name(dir: &Path, moc_stem: &str) -> Option<String> {
+    [format!("{}.json", moc_stem), format!("{}.model.json", moc_stem), "model.json".to_string()]
+        .into_iter()
+        .find(|name| dir.join(name).is_file())
+}
+
+pub(crate) fn resolve_cubism2(dir: &Path, moc_file: &str, moc_stem: &str, scene: &mut SceneData) {
+    let header = read_header(&dir.join(moc_file), 3);
+    if header.as_slice() == b"moc" {
+        scene.version = Some("2.1".to_string());
+    } else {
+        scene.add_diagnostic(
+            Severity::Warning,
+            "invalidMoc",
+            format!("{} does not have a valid moc header", moc_file),
+            Some(scene.scene_path(moc_file)),
+        );
+    }
+    let model_file = cubism2_model_json_name(dir, moc_stem).unwrap_or_else(|| format!("{}.json", moc_stem));
+    let Some(model) = read_model_json(dir, &model_file, moc_file, scene) else {
+        return;
+    };
+    for key in ["model", "physics", "pose"] {
+        if let Some(file) = model.get(key).and_then(|v| v.as_str()) {
+            scene.add_asset(dir, file);
+        }
+    }
+    if let Some(textures) = model.get("textures").and_then(|v| v.as_array()) {
+        for file in textures.iter().filter_map(|v| v.as_str()) {
+            scene.add_asset(dir, file);
+        }
+    }
+    if let Some(expressions) = model.get("expressions").and_then(|v| v.as_array()) {
+        for file in expressions.iter().filter_map(|v| v.get("file")?.as_str()) {
+            scene.add_asset(dir, file);
+        }
+    }
+    if let Some(groups) = model.get("motions").and_then(|v| v.as_object()) {
+        for motions in groups.values().filter_map(|v| v.as_array()) {
+            for motion in motions {
+                for key in ["file", "sound"] {
+                    if let Some(file) = motion.get(key).and_then(|v| v.as_str()) {
+                        scene.add_asset(dir, file);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn find_case_insensitive(dir: &Path, rel_path: &str) -> Option<String> {
+    let mut current = dir.to_path_buf();
+    let mut resolved = Vec::new();
+    for component in rel_path.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        if component == ".." {
+            current.pop();
+            resolved.push(component.to_string());
+            continue;
+        }
+        let component_name = normalize_name(component);
+        let entry_name = fs::read_dir(&current)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .find(|name| normalize_name(name) == component_name)?;
+        current.push(&entry_name);
+        resolved.push(entry_name);
+    }
+    if current.is_file() {
+        Some(resolved.join("/"))
+    } else {
+        None
+    }
+}
+
+fn validate_atlas_page(dir: &Path, page: &AtlasPage, page_path: &str, scene: &mut SceneData) {
+    let Some(asset_path) = scene.push_asset(page_path) else {
+        return;
+    };
+    let actual_path = if dir.join(page_path).is_file() {
+        page_path.to_string()
+    } else if let Some(actual) = find_case_insensitive(dir, page_path) {
+        scene.add_diagnostic(
+            Severity::Warning,
+            "pageCaseMismatch",
+            format!("Atlas page {} only matches {} when ignoring case", page.name, actual),
+            Some(asset_path),
+        );
+        actual
+    } else {
+        scene.add_diagnostic(
+            Severity::Error,
+            "missingPage",
+            format!("Atlas page image not found: {}", page.name),
+            Some(asset_path),
+        );
+        return;
+    };
+    if page.width == 0 || page.height == 0 {
+        return;
+    }
+    match image::image_dimensions(dir.join(&actual_path)) {
+        Ok((width, height)) if width != page.width || height != page.height => {
+            scene.add_diagnostic(
+                Severity::Warning,
+                "pageSizeMismatch",
+                format!(
+                    "Atlas page {} is declared as {}x{} but the image is {}x{}",
+                    page.name, page.width, page.height, width, height
+                ),
+                Some(scene.scene_path(&actual_path)),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => {
+            scene.add_diagnostic(
+                Severity::Error,
+                "unreadablePage",
+                format!("Failed to read atlas page {}: {}", page.name, e),
+                Some(scene.scene_path(&actual_path)),
+            );
+        }
+    }
+}
+
+fn resolve_skeleton(dir: &Path, skeleton_file: &str, scene: &mut SceneData) {
+    scene.add_asset(dir, skeleton_file);
+    match spine::read_skeleton_header(&dir.join(skeleton_file)) {
+        Ok(header) => {
+            if spine::runtime_version(&header.version).is_none() {
+                scene.add_diagnostic(
+                    Severity::Error,
+                    "unsupportedVersion",
+                    format!(
+                        "Spine {} is not supported (supported: {})",
+                        header.version,
+                        spine::SUPPORTED_SPINE_VERSIONS.join(", ")
+                    ),
+                    Some(scene.scene_path(skeleton_file)),
+                );
+            }
+            scene.version = Some(header.version);
+        }
+        Err(e) => {
+            scene.add_diagnostic(
+                Severity::Warning,
+                "unknownVersion",
+                format!("Failed to detect Spine version of {}: {}", skeleton_file, e),
+                Some(scene.scene_path(skeleton_file)),
+            );
+        }
+    }
+}
+
+pub(crate) fn resolve_spine(dir: &Path, skeleton_file: &str, atlas_file: &str, scene: &mut SceneData) {
+    resolve_skeleton(dir, skeleton_file, scene);
+    scene.add_asset(dir, atlas_file);
+    let atlas = match Atlas::read(&dir.join(atlas_file)) {
+        Ok(atlas) => atlas,
+        Err(e) => {
+            scene.add_diagnostic(
+                Severity::Error,
+                "invalidAtlas",
+                format!("Failed to parse {}: {}", atlas_file, e),
+                Some(scene.scene_path(atlas_file)),
+            );
+            return;
+        }
+    };
+    let atlas_dir = match atlas_file.rfind('/') {
+        Some(idx) => &atlas_file[..=idx],
+        None => "",
+    };
+    for page in &atlas.pages {
+        let page_path = format!("{}{}", atlas_dir, page.name.replace('\\', "/"));
+        validate_atlas_page(dir, page, &page_path, scene);
+    }
+    let atlas_name = &atlas_file[atlas_dir.len()..];
+    let repacked = overlay::find_newer(&dir.join(atlas_dir), atlas_name)
+        .and_then(|path| Some((Atlas::read(&path).ok()?, path)));
+    match repacked {
+        Some((repacked, path)) => {
+            scene.add_overlay(atlas_file, &path);
+            scene.add_diagnostic(
+                Severity::Warning,
+                "repackedAtlas",
+                format!("{} was repacked, using the copy from the overlay", atlas_file),
+                Some(scene.scene_path(atlas_file)),
+            );
+            scene.atlas_pages.extend(repacked.pages);
+        }
+        None => scene.atlas_pages.extend(atlas.pages),
+    }
+}
+
+pub(crate) fn resolve_generated_spine(
+    dir: &Path,
+    skeleton_file: &str,
+    images: &[String],
+    atlas_file: &str,
+    scene: &mut SceneData,
+) {
+    resolve_skeleton(dir, skeleton_file, scene);
+    for image in images {
+        scene.add_asset(dir, image);
+    }
+    scene.add_diagnostic(
+        Severity::Warning,
+        "generatedAtlas",
+        format!("{} has no atlas, using one packed from its images", skeleton_file),
+        Some(scene.scene_path(atlas_file)),
+    );
+}
+
+pub(crate) fn apply_generated_atlas(atlas_file: &str, atlas_path: &Path, atlas: Atlas, scene: &mut SceneData) {
+    scene.add_overlay(atlas_file, atlas_path);
+    scene.atlas_pages.extend(atlas.pages);
+}
+
+pub(crate) fn read_spine_metadata(dir: &Path, skeleton_file: &str, scene: &mut SceneData) {
+    match spine::read_metadata(&dir.join(skeleton_file)) {
+        Ok(metadata) => scene.metadata = Some(metadata),
+        Err(e) => scene.add_diagnostic(
+            Severity::Warning,
+            "invalidSkeleton",
+            format!("Failed to read metadata from {}: {}", skeleton_file, e),
+            Some(scene.scene_path(skeleton_file)),
+        ),
+    }
+}
+
+pub(crate) fn resolve_layered_sprite(dir: &Path, meta_file: &str, scene: &mut SceneData) {
+    scene.add_asset(dir, meta_file);
+    let meta = match read_json(dir, meta_file, scene) {
+        Some(v) => v,
+        None => return,
+    };
+    let mut textures = Vec::new();
+    textures.extend(meta.get("atlas").and_then(|v| v.as_str()));
+    textures.extend(meta.pointer("/bodySpriteRect/texture").and_then(|v| v.as_str()));
+    if let Some(faces) = meta.get("faces").and_then(|v| v.as_object()) {
+        textures.extend(faces.values().filter_map(|v| v.get("texture")?.as_str()));
+    }
+    for texture in textures.into_iter().filter(|t| !t.is_empty()) {
+        scene.add_asset(dir, texture);
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use super::RuntimeVersion;
+use super::data::*;
+use super::pose::LocalTransform;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Pose {
+    pub(crate) locals: Vec<LocalTransform>,
+    pub(crate) colors: Vec<Color>,
+    pub(crate) attachments: Vec<Option<String>>,
+    pub(crate) draw_order: Vec<usize>,
+    pub(crate) deforms: HashMap<(usize, String), Vec<f32>>,
+}
+
+fn wrap_degrees(degrees: f32) -> f32 {
+    degrees - (degrees / 360.0).round() * 360.0
+}
+
+fn sample(frames: &[Keyframe], time: f32, shared_curve: bool, shortest_rotation: bool) -> Option<Vec<f32>> {
+    let index = frames.iter().rposition(|frame| frame.time <= time)?;
+    let frame = &frames[index];
+    let Some(next) = frames.get(index + 1) else {
+        return Some(frame.values.clone());
+    };
+    let span = next.time - frame.time;
+    let x = if span > 0.0 { (time - frame.time) / span } else { 0.0 };
+    let values = frame
+        .values
+        .iter()
+        .zip(&next.values)
+        .enumerate()
+        .map(|(channel, (from, to))| {
+            let percent = frame.curve.percent(if shared_curve { 0 } else { channel }, x);
+            let delta = if shortest_rotation { wrap_degrees(to - from) } else { to - from };
+            from + delta * percent
+        })
+        .collect();
+    Some(values)
+}
+
+fn step<T>(frames: &[(f32, T)], time: f32) -> Option<&T> {
+    frames.iter().rev().find(|(t, _)| *t <= time).map(|(_, value)| value)
+}
+
+impl Pose {
+    pub(crate) fn setup(skeleton: &SkeletonData) -> Self {
+        Self {
+            locals: skeleton.bones.iter().map(LocalTransform::setup).collect(),
+            colors: skeleton.slots.iter().map(|slot| slot.color).collect(),
+            attachments: skeleton.slots.iter().map(|slot| slot.attachment.clone()).collect(),
+            draw_order: (0..skeleton.slots.len()).collect(),
+            deforms: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, skeleton: &SkeletonData, animation: &Animation, time: f32) {
+        let legacy = RuntimeVersion::parse(&skeleton.version).is_some_and(|v| v < RuntimeVersion::V40);
+        for timeline in &animation.timelines {
+            match timeline {
+                Timeline::Bone { bone, property, frames } => {
+                    let (Some(local), Some(setup)) = (self.locals.get_mut(*bone), skeleton.bones.get(*bone)) else {
+                        continue;
+                    };
+                    let rotate = *property == BoneProperty::Rotate;
+                    let Some(values) = sample(frames, time, legacy, legacy && rotate) else {
+                        continue;
+                    };
+                    let first = values.first().copied().unwrap_or(0.0);
+                    let second = values.get(1).copied().unwrap_or(first);
+                    match property {
+                        BoneProperty::Rotate => local.rotation = setup.rotation + first,
+                        BoneProperty::Translate => {
+                            local.x = setup.x + first;
+                            local.y = setup.y + second;
+                        }
+                        BoneProperty::TranslateX => local.x = setup.x + first,
+                        BoneProperty::TranslateY => local.y = setup.y + first,
+                        BoneProperty::Scale => {
+                            local.scale_x = setup.scale_x * first;
+                            local.scale_y = setup.scale_y * second;
+                        }
+                        BoneProperty::ScaleX => local.scale_x = setup.scale_x * first,
+                        BoneProperty::ScaleY => local.scale_y = setup.scale_y * first,
+                        BoneProperty::Shear => {
+                            local.shear_x = setup.shear_x + first;
+                            local.shear_y = setup.shear_y + second;
+                        }
+                        BoneProperty::ShearX => local.shear_x = setup.shear_x + first,
+                        BoneProperty::ShearY => local.shear_y = setup.shear_y + first,
+                    }
+                }
+                Timeline::Color { slot, property, frames } => {
+                    let Some(color) = self.colors.get_mut(*slot) else {
+                        continue;
+                    };
+                    let Some(values) = sample(frames, time, legacy, false) else {
+                        continue;
+                    };
+                    let value = |i: usize| values.get(i).copied().unwrap_or(1.0).clamp(0.0, 1.0);
+                    match property {
+                        ColorProperty::Rgba => {
+                            *color = Color {
+                                r: value(0),
+                                g: value(1),
+                                b: value(2),
+                                a: value(3),
+                            }
+                        }
+                        ColorProperty::Rgb => {
+                            color.r = value(0);
+                            color.g = value(1);
+                            color.b = value(2);
+                        }
+                        ColorProperty::Alpha => color.a = value(0),
+                    }
+                }
+                Timeline::Attachment { slot, frames } => {
+                    if let (Some(attachment), Some(name)) = (self.attachments.get_mut(*slot), step(frames, time)) {
+                        attachment.clone_from(name);
+                    }
+                }
+                Timeline::Deform {
+                    slot,
+                    attachment,
+                    frames,
+                } => {
+                    if let Some(values) = sample(frames, time, true, false) {
+                        self.deforms.insert((*slot, attachment.clone()), values);
+                    }
+                }
+                Timeline::DrawOrder { frames } => {
+                    if let Some(order) = step(frames, time) {
+                        self.draw_order = order.clone().unwrap_or_else(|| (0..skeleton.slots.len()).collect());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1213 @@
+use std::collections::HashSet;
+
+use super::RuntimeVersion;
+use super::data::*;
+
+const CURVE_STEPPED: u8 = 1;
+const CURVE_BEZIER: u8 = 2;
+
+#[derive(Clone, Copy)]
+enum FrameValues {
+    Floats(usize),
+    Bytes(usize, usize),
+    LegacyColor(bool),
+}
+
+fn frame_times(frames: &[Keyframe]) -> Vec<f32> {
+    frames.iter().map(|frame| frame.time).collect()
+}
+
+pub(crate) struct BinaryInput<'a> {
+    data: &'a [u8],
+    pos: usize,
+    strings: Vec<String>,
+}
+
+impl<'a> BinaryInput<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            strings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn skip(&mut self, len: usize) -> Result<(), String> {
+        if self.pos + len > self.data.len() {
+            return Err("Unexpected end of skeleton data".to_string());
+        }
+        self.pos += len;
+        Ok(())
+    }
+
+    pub(crate) fn read_byte(&mut self) -> Result<u8, String> {
+        let b = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| "Unexpected end of skeleton data".to_string())?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn read_bool(&mut self) -> Result<bool, String> {
+        Ok(self.read_byte()? != 0)
+    }
+
+    fn read_int(&mut self) -> Result<i32, String> {
+        if self.pos + 4 > self.data.len() {
+            return Err("Unexpected end of skeleton data".to_string());
+        }
+        let bytes = [
+            self.data[self.pos],
+            self.data[self.pos + 1],
+            self.data[self.pos + 2],
+            self.data[self.pos + 3],
+        ];
+        self.pos += 4;
+        Ok(i32::from_be_bytes(bytes))
+    }
+
+    fn read_float(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.read_int()? as u32))
+    }
+
+    pub(crate) fn read_varint(&mut self, optimize_positive: bool) -> Result<i32, String> {
+        let mut result: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let b = self.read_byte()?;
+            result |= ((b & 0x7F) as u32) << shift;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        if optimize_positive {
+            Ok(result as i32)
+        } else {
+            Ok(((result >> 1) as i32) ^ -((result & 1) as i32))
+        }
+    }
+
+    fn read_count(&mut self) -> Result<usize, String> {
+        let n = self.read_varint(true)?;
+        if n < 0 || n as usize > self.data.len() {
+            return Err("Invalid count in skeleton data".to_string());
+        }
+        Ok(n as usize)
+    }
+
+    pub(crate) fn read_string(&mut self) -> Result<Option<String>, String> {
+        let byte_count = self.read_varint(true)?;
+        match byte_count {
+            0 => Ok(None),
+            1 => Ok(Some(String::new())),
+            n if n < 0 => Err("Invalid string length in skeleton data".to_string()),
+            n => {
+                let len = (n - 1) as usize;
+                if self.pos + len > self.data.len() {
+                    return Err("Unexpected end of skeleton data".to_string());
+                }
+                let s = String::from_utf8_lossy(&self.data[self.pos..self.pos + len]).into_owned();
+                self.pos += len;
+                Ok(Some(s))
+            }
+        }
+    }
+
+    fn read_string_ref(&mut self) -> Result<Option<String>, String> {
+        let index = self.read_count()?;
+        if index == 0 {
+            return Ok(None);
+        }
+        self.strings
+            .get(index - 1)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| "Invalid string reference in skeleton data".to_string())
+    }
+
+    fn read_floats(&mut self, n: usize) -> Result<Vec<f32>, String> {
+        if self.pos + n * 4 > self.data.len() {
+            return Err("Unexpected end of skeleton data".to_string());
+        }
+        (0..n).map(|_| self.read_float()).collect()
+    }
+
+    fn read_color(&mut self) -> Result<Color, String> {
+        Ok(Color::from_rgba8888(self.read_int()? as u32))
+    }
+}
+
+struct SkeletonReader<'a> {
+    input: BinaryInput<'a>,
+    version: RuntimeVersion,
+    nonessential: bool,
+    linked_meshes: Vec<LinkedMesh>,
+    region_paths: HashSet<String>,
+}
+
+pub(crate) fn read_skeleton_data(data: &[u8], version: &str) -> Result<SkeletonData, String> {
+    let runtime = RuntimeVersion::parse(version)
+        .ok_or_else(|| format!("Unsupported Spine version: {}", version))?;
+    let mut reader = SkeletonReader {
+        input: BinaryInput::new(data),
+        version: runtime,
+        nonessential: false,
+        linked_meshes: Vec::new(),
+        region_paths: HashSet::new(),
+    };
+    reader.read_skeleton(version)
+}
+
+impl SkeletonReader<'_> {
+    fn at_least(&self, version: RuntimeVersion) -> bool {
+        self.version >= version
+    }
+
+    fn read_name(&mut self) -> Result<Option<String>, String> {
+        if self.at_least(RuntimeVersion::V38) {
+            self.input.read_string_ref()
+        } else {
+            self.input.read_string()
+        }
+    }
+
+    fn skip_index_list(&mut self) -> Result<(), String> {
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            self.input.read_varint(true)?;
+        }
+        Ok(())
+    }
+
+    fn skip_flagged_floats(&mut self, flags: u8, bits: &[u8]) -> Result<(), String> {
+        for bit in bits {
+            if flags & bit != 0 {
+                self.input.skip(4)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_skeleton(&mut self, version: &str) -> Result<SkeletonData, String> {
+        let mut skeleton = SkeletonData {
+            version: version.to_string(),
+            ..Default::default()
+        };
+        if self.at_least(RuntimeVersion::V40) {
+            self.input.skip(8)?;
+        } else {
+            self.input.read_string()?;
+        }
+        self.input.read_string()?;
+        if self.at_least(RuntimeVersion::V38) {
+            skeleton.x = self.input.read_float()?;
+            skeleton.y = self.input.read_float()?;
+        }
+        skeleton.width = self.input.read_float()?;
+        skeleton.height = self.input.read_float()?;
+        if self.at_least(RuntimeVersion::V42) {
+            self.input.skip(4)?;
+        }
+        self.nonessential = self.input.read_bool()?;
+        if self.nonessential {
+            skeleton.fps = Some(self.input.read_float()?);
+            skeleton.images = self.input.read_string()?;
+            if self.at_least(RuntimeVersion::V37) {
+                self.input.read_string()?;
+            }
+        }
+        if self.at_least(RuntimeVersion::V38) {
+            let n = self.input.read_count()?;
+            for _ in 0..n {
+                let s = self.input.read_string()?.unwrap_or_default();
+                self.input.strings.push(s);
+            }
+        }
+
+        let n = self.input.read_count()?;
+        for i in 0..n {
+            let bone = self.read_bone(i)?;
+            skeleton.bones.push(bone);
+        }
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            let slot = self.read_slot()?;
+            skeleton.slots.push(slot);
+        }
+        let n = self.input.read_count()?;
+        skeleton.constraints += n;
+        for _ in 0..n {
+            self.read_ik_constraint()?;
+        }
+        let n = self.input.read_count()?;
+        skeleton.constraints += n;
+        for _ in 0..n {
+            self.read_transform_constraint()?;
+        }
+        let n = self.input.read_count()?;
+        skeleton.constraints += n;
+        for _ in 0..n {
+            self.read_path_constraint()?;
+        }
+        if self.at_least(RuntimeVersion::V42) {
+            let n = self.input.read_count()?;
+            skeleton.constraints += n;
+            for _ in 0..n {
+                self.read_physics_constraint()?;
+            }
+        }
+
+        if let Some(skin) = self.read_skin(skeleton.skins.len(), true)? {
+            skeleton.skins.push(skin);
+        }
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            if let Some(skin) = self.read_skin(skeleton.skins.len(), false)? {
+                skeleton.skins.push(skin);
+            }
+        }
+        let linked_meshes = std::mem::take(&mut self.linked_meshes);
+        skeleton.resolve_linked_meshes(linked_meshes)?;
+        skeleton.region_paths = std::mem::take(&mut self.region_paths);
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            let event = self.read_event_data()?;
+            skeleton.events.push(event);
+        }
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            let name = self.input.read_string()?.unwrap_or_default();
+            let animation = self.read_animation(name, &skeleton)?;
+            skeleton.animations.push(animation);
+        }
+        Ok(skeleton)
+    }
+
+    fn read_bone(&mut self, index: usize) -> Result<BoneData, String> {
+        let name = self.input.read_string()?.unwrap_or_default();
+        let parent = if index == 0 {
+            None
+        } else {
+            Some(self.input.read_count()?)
+        };
+        let rotation = self.input.read_float()?;
+        let x = self.input.read_float()?;
+        let y = self.input.read_float()?;
+        let scale_x = self.input.read_float()?;
+        let scale_y = self.input.read_float()?;
+        let shear_x = self.input.read_float()?;
+        let shear_y = self.input.read_float()?;
+        self.input.skip(4)?;
+        let transform_mode = if self.at_least(RuntimeVersion::V42) {
+            TransformMode::from_index(self.input.read_byte()? as i32)
+        } else {
+            TransformMode::from_index(self.input.read_varint(true)?)
+        };
+        if self.at_least(RuntimeVersion::V38) {
+            self.input.skip(1)?;
+        }
+        if self.nonessential {
+            self.input.skip(4)?;
+            if self.at_least(RuntimeVersion::V42) {
+                self.input.read_string()?;
+                self.input.skip(1)?;
+            }
+        }
+        Ok(BoneData {
+            name,
+            parent,
+            x,
+            y,
+            rotation,
+            scale_x,
+            scale_y,
+            shear_x,
+            shear_y,
+            transform_mode,
+        })
+    }
+
+    fn read_slot(&mut self) -> Result<SlotData, String> {
+        let name = self.input.read_string()?.unwrap_or_default();
+        let bone = self.input.read_count()?;
+        let color = self.input.read_color()?;
+        self.input.skip(4)?;
+        let attachment = self.read_name()?;
+        let blend = BlendMode::from_index(self.input.read_varint(true)?);
+        if self.nonessential && self.at_least(RuntimeVersion::V42) {
+            self.input.skip(1)?;
+        }
+        Ok(SlotData {
+            name,
+            bone,
+            attachment,
+            color,
+            blend,
+        })
+    }
+
+    fn read_constraint_head(&mut self, skin_required: bool) -> Result<(), String> {
+        self.input.read_string()?;
+        self.input.read_varint(true)?;
+        if skin_required {
+            self.input.skip(1)?;
+        }
+        self.skip_index_list()?;
+        self.input.read_varint(true)?;
+        Ok(())
+    }
+
+    fn read_ik_constraint(&mut self) -> Result<(), String> {
+        let v = self.version;
+        self.read_constraint_head(v >= RuntimeVersion::V38 && v < RuntimeVersion::V42)?;
+        if v >= RuntimeVersion::V42 {
+            let flags = self.input.read_byte()?;
+            if flags & 32 != 0 && flags & 64 != 0 {
+                self.input.skip(4)?;
+            }
+            return self.skip_flagged_floats(flags, &[128]);
+        }
+        let floats = if v >= RuntimeVersion::V38 { 2 } else { 1 };
+        let flags = if v >= RuntimeVersion::V37 { 3 } else { 0 };
+        self.input.skip(floats * 4 + 1 + flags)
+    }
+
+    fn read_transform_constraint(&mut self) -> Result<(), String> {
+        let v = self.version;
+        self.read_constraint_head(v >= RuntimeVersion::V38 && v < RuntimeVersion::V42)?;
+        if v >= RuntimeVersion::V42 {
+            let flags = self.input.read_byte()?;
+            self.skip_flagged_floats(flags, &[8, 16, 32, 64, 128])?;
+            let flags = self.input.read_byte()?;
+            return self.skip_flagged_floats(flags, &[1, 2, 4, 8, 16, 32, 64]);
+        }
+        let mixes = if v >= RuntimeVersion::V40 { 6 } else { 4 };
+        self.input.skip(2 + (6 + mixes) * 4)
+    }
+
+    fn read_path_constraint(&mut self) -> Result<(), String> {
+        let v = self.version;
+        self.read_constraint_head(v >= RuntimeVersion::V38)?;
+        if v >= RuntimeVersion::V42 {
+            let flags = self.input.read_byte()?;
+            self.skip_flagged_floats(flags, &[128])?;
+            return self.input.skip(5 * 4);
+        }
+        for _ in 0..3 {
+            self.input.read_varint(true)?;
+        }
+        let mixes = if v >= RuntimeVersion::V40 { 3 } else { 2 };
+        self.input.skip((3 + mixes) * 4)
+    }
+
+    fn read_physics_constraint(&mut self) -> Result<(), String> {
+        self.input.read_string()?;
+        self.input.read_varint(true)?;
+        self.input.read_varint(true)?;
+        let flags = self.input.read_byte()?;
+        self.skip_flagged_floats(flags, &[2, 4, 8, 16, 32, 64])?;
+        self.input.skip(1 + 3 * 4)?;
+        self.skip_flagged_floats(flags, &[128])?;
+        self.input.skip(2 * 4)?;
+        let flags = self.input.read_byte()?;
+        self.skip_flagged_floats(flags, &[128])
+    }
+
+    fn read_skin(&mut self, skin_index: usize, default_skin: bool) -> Result<Option<Skin>, String> {
+        let name;
+        let slot_count;
+        if default_skin {
+            slot_count = self.input.read_count()?;
+            if slot_count == 0 {
+                return Ok(None);
+            }
+            name = "default".to_string();
+        } else if !self.at_least(RuntimeVersion::V38) {
+            name = self.input.read_string()?.unwrap_or_default();
+            slot_count = self.input.read_count()?;
+        } else {
+            if self.at_least(RuntimeVersion::V42) {
+                name = self.input.read_string()?.unwrap_or_default();
+                if self.nonessential {
+                    self.input.skip(4)?;
+                }
+            } else {
+                name = self.input.read_string_ref()?.unwrap_or_default();
+            }
+            let lists = if self.at_least(RuntimeVersion::V42) { 5 } else { 4 };
+            for _ in 0..lists {
+                self.skip_index_list()?;
+            }
+            slot_count = self.input.read_count()?;
+        }
+        let mut skin = Skin {
+            name,
+            ..Default::default()
+        };
+        for _ in 0..slot_count {
+            let slot = self.input.read_count()?;
+            let n = self.input.read_count()?;
+            for _ in 0..n {
+                let placeholder = self.read_name()?.unwrap_or_default();
+                let attachment = if self.at_least(RuntimeVersion::V42) {
+                    self.read_attachment_42(skin_index, slot, &placeholder)?
+                } else {
+                    self.read_attachment(skin_index, slot, &placeholder)?
+                };
+                if let Some(attachment) = attachment {
+                    skin.attachments.insert((slot, placeholder), attachment);
+                }
+            }
+        }
+        Ok(Some(skin))
+    }
+
+    fn read_vertices(&mut self, vertex_count: usize, weighted: bool) -> Result<Vertices, String> {
+        if !weighted {
+            return Ok(Vertices {
+                count: vertex_count,
+                bones: Vec::new(),
+                values: self.input.read_floats(vertex_count * 2)?,
+            });
+        }
+        let mut bones = Vec::new();
+        let mut values = Vec::new();
+        for _ in 0..vertex_count {
+            let bone_count = self.input.read_count()?;
+            bones.push(bone_count);
+            for _ in 0..bone_count {
+                bones.push(self.input.read_count()?);
+                values.extend(self.input.read_floats(3)?);
+            }
+        }
+        Ok(Vertices {
+            count: vertex_count,
+            bones,
+            values,
+        })
+    }
+
+    fn read_legacy_vertices(&mut self) -> Result<Vertices, String> {
+        let vertex_count = self.input.read_count()?;
+        let weighted = self.input.read_bool()?;
+        self.read_vertices(vertex_count, weighted)
+    }
+
+    fn skip_short_array(&mut self) -> Result<(), String> {
+        let n = self.input.read_count()?;
+        self.input.skip(n * 2)
+    }
+
+    fn read_short_array(&mut self) -> Result<Vec<usize>, String> {
+        let n = self.input.read_count()?;
+        let mut values = Vec::with_capacity(n);
+        for _ in 0..n {
+            let high = self.input.read_byte()? as usize;
+            values.push(high << 8 | self.input.read_byte()? as usize);
+        }
+        Ok(values)
+    }
+
+    fn read_varint_array(&mut self, n: usize) -> Result<Vec<usize>, String> {
+        (0..n).map(|_| self.input.read_count()).collect()
+    }
+
+    fn read_sequence(&mut self, path: String) -> Result<String, String> {
+        let count = self.input.read_varint(true)?;
+        let start = self.input.read_varint(true)?;
+        let digits = self.input.read_count()?;
+        let setup = self.input.read_varint(true)?;
+        self.region_paths
+            .extend((start..start + count).map(|index| sequence_path(&path, index, digits)));
+        Ok(sequence_path(&path, start + setup, digits))
+    }
+
+    fn finish_region_path(&mut self, path: String, sequence: bool) -> Result<String, String> {
+        if sequence {
+            return self.read_sequence(path);
+        }
+        self.region_paths.insert(path.clone());
+        Ok(path)
+    }
+
+    fn skip_varints(&mut self, n: usize) -> Result<(), String> {
+        for _ in 0..n {
+            self.input.read_varint(true)?;
+        }
+        Ok(())
+    }
+
+    fn skip_nonessential_color(&mut self) -> Result<(), String> {
+        if self.nonessential {
+            self.input.skip(4)?;
+        }
+        Ok(())
+    }
+
+    fn read_region(&mut self, path: String, rotation: Option<f32>) -> Result<RegionAttachment, String> {
+        let rotation = match rotation {
+            Some(r) => r,
+            None => self.input.read_float()?,
+        };
+        Ok(RegionAttachment {
+            path,
+            color: Color::WHITE,
+            rotation,
+            x: self.input.read_float()?,
+            y: self.input.read_float()?,
+            scale_x: self.input.read_float()?,
+            scale_y: self.input.read_float()?,
+            width: self.input.read_float()?,
+            height: self.input.read_float()?,
+        })
+    }
+
+    fn read_region_path(&mut self, name: &str) -> Result<String, String> {
+        Ok(self.read_name()?.unwrap_or_else(|| name.to_string()))
+    }
+
+    fn read_attachment(&mut self, skin: usize, slot: usize, placeholder: &str) -> Result<Option<Attachment>, String> {
+        let v41 = self.at_least(RuntimeVersion::V41);
+        let name = self.read_name()?.unwrap_or_else(|| placeholder.to_string());
+        let attachment = match self.input.read_byte()? {
+            0 => {
+                let path = self.read_region_path(&name)?;
+                let mut region = self.read_region(path, None)?;
+                region.color = self.input.read_color()?;
+                let sequence = v41 && self.input.read_bool()?;
+                region.path = self.finish_region_path(region.path, sequence)?;
+                Attachment::Region(region)
+            }
+            1 => {
+                self.read_legacy_vertices()?;
+                self.skip_nonessential_color()?;
+                Attachment::Other
+            }
+            2 => {
+                let mut path = self.read_region_path(&name)?;
+                let color = self.input.read_color()?;
+                let vertex_count = self.input.read_count()?;
+                let uvs = self.input.read_floats(vertex_count * 2)?;
+                let triangles = self.read_short_array()?;
+                let weighted = self.input.read_bool()?;
+                let vertices = self.read_vertices(vertex_count, weighted)?;
+                self.input.read_varint(true)?;
+                let sequence = v41 && self.input.read_bool()?;
+                path = self.finish_region_path(path, sequence)?;
+                if self.nonessential {
+                    self.skip_short_array()?;
+                    self.input.skip(8)?;
+                }
+                Attachment::Mesh(MeshAttachment {
+                    path,
+                    color,
+                    vertices,
+                    uvs,
+                    triangles,
+                })
+            }
+            3 => {
+                let mut path = self.read_region_path(&name)?;
+                let color = self.input.read_color()?;
+                let parent_skin = self.read_name()?.map(LinkedSkin::Name);
+                let parent = self.read_name()?.unwrap_or_default();
+                self.input.skip(1)?;
+                let sequence = v41 && self.input.read_bool()?;
+                path = self.finish_region_path(path, sequence)?;
+                if self.nonessential {
+                    self.input.skip(8)?;
+                }
+                self.linked_meshes.push(LinkedMesh {
+                    skin,
+                    slot,
+                    name: placeholder.to_string(),
+                    parent_skin,
+                    parent,
+                    path,
+                    color,
+                });
+                return Ok(None);
+            }
+            4 => {
+                self.input.skip(2)?;
+                let vertices = self.read_legacy_vertices()?;
+                self.input.skip(vertices.count / 3 * 4)?;
+                self.skip_nonessential_color()?;
+                Attachment::Other
+            }
+            5 => {
+                self.input.skip(3 * 4)?;
+                self.skip_nonessential_color()?;
+                Attachment::Other
+            }
+            6 => {
+                let end_slot = self.input.read_count()?;
+                let vertices = self.read_legacy_vertices()?;
+                self.skip_nonessential_color()?;
+                Attachment::Clipping(ClippingAttachment {
+                    end_slot: Some(end_slot),
+                    vertices,
+                })
+            }
+            t => return Err(format!("Unknown attachment type {} for {}", t, name)),
+        };
+        Ok(Some(attachment))
+    }
+
+    fn read_attachment_42(&mut self, skin: usize, slot: usize, placeholder: &str) -> Result<Option<Attachment>, String> {
+        let flags = self.input.read_byte()?;
+        let name = if flags & 8 != 0 {
+            self.input.read_string_ref()?.unwrap_or_else(|| placeholder.to_string())
+        } else {
+            placeholder.to_string()
+        };
+        let attachment_type = flags & 7;
+        let mut path = name.clone();
+        let mut color = Color::WHITE;
+        if matches!(attachment_type, 0 | 2 | 3) {
+            if flags & 16 != 0
+                && let Some(explicit) = self.input.read_string_ref()?
+            {
+                path = explicit;
+            }
+            if flags & 32 != 0 {
+                color = self.input.read_color()?;
+            }
+            path = self.finish_region_path(path, flags & 64 != 0)?;
+        }
+        let attachment = match attachment_type {
+            0 => {
+                let rotation = if flags & 128 != 0 { self.input.read_float()? } else { 0.0 };
+                let mut region = self.read_region(path, Some(rotation))?;
+                region.color = color;
+                Attachment::Region(region)
+            }
+            1 => {
+                let vertex_count = self.input.read_count()?;
+                self.read_vertices(vertex_count, flags & 16 != 0)?;
+                self.skip_nonessential_color()?;
+                Attachment::Other
+            }
+            2 => {
+                let hull_length = self.input.read_count()?;
+                let vertex_count = self.input.read_count()?;
+                let vertices = self.read_vertices(vertex_count, flags & 128 != 0)?;
+                let uvs = self.input.read_floats(vertex_count * 2)?;
+                let triangles = self.read_varint_array((vertex_count * 2).saturating_sub(hull_length + 2) * 3)?;
+                if self.nonessential {
+                    let n = self.input.read_count()?;
+                    self.skip_varints(n)?;
+                    self.input.skip(8)?;
+                }
+                Attachment::Mesh(MeshAttachment {
+                    path,
+                    color,
+                    vertices,
+                    uvs,
+                    triangles,
+                })
+            }
+            3 => {
+                let parent_skin = LinkedSkin::Index(self.input.read_count()?);
+                let parent = self.input.read_string_ref()?.unwrap_or_default();
+                if self.nonessential {
+                    self.input.skip(8)?;
+                }
+                self.linked_meshes.push(LinkedMesh {
+                    skin,
+                    slot,
+                    name: placeholder.to_string(),
+                    parent_skin: Some(parent_skin),
+                    parent,
+                    path,
+                    color,
+                });
+                return Ok(None);
+            }
+            4 => {
+                let vertex_count = self.input.read_count()?;
+                self.read_vertices(vertex_count, flags & 64 != 0)?;
+                self.input.skip(vertex_count / 3 * 4)?;
+                self.skip_nonessential_color()?;
+                Attachment::Other
+            }
+            5 => {
+                self.input.skip(3 * 4)?;
+                self.skip_nonessential_color()?;
+                Attachment::Other
+            }
+            6 => {
+                let end_slot = self.input.read_count()?;
+                let vertex_count = self.input.read_count()?;
+                let vertices = self.read_vertices(vertex_count, flags & 16 != 0)?;
+                self.skip_nonessential_color()?;
+                Attachment::Clipping(ClippingAttachment {
+                    end_slot: Some(end_slot),
+                    vertices,
+                })
+            }
+            t => return Err(format!("Unknown attachment type {} for {}", t, name)),
+        };
+        Ok(Some(attachment))
+    }
+
+    fn read_event_data(&mut self) -> Result<EventData, String> {
+        let name = if self.at_least(RuntimeVersion::V42) {
+            self.input.read_string()?
+        } else {
+            self.read_name()?
+        };
+        self.input.read_varint(false)?;
+        self.input.skip(4)?;
+        self.input.read_string()?;
+        let mut audio_path = None;
+        if self.at_least(RuntimeVersion::V37) {
+            audio_path = self.input.read_string()?;
+            if audio_path.is_some() {
+                self.input.skip(8)?;
+            }
+        }
+        Ok(EventData {
+            name: name.unwrap_or_default(),
+            audio_path,
+        })
+    }
+
+    fn skip_legacy_curve(&mut self) -> Result<(), String> {
+        if self.input.read_byte()? == CURVE_BEZIER {
+            self.input.skip(4 * 4)?;
+        }
+        Ok(())
+    }
+
+    fn skip_curve(&mut self, channels: usize) -> Result<(), String> {
+        if self.input.read_byte()? == CURVE_BEZIER {
+            self.input.skip(channels * 4 * 4)?;
+        }
+        Ok(())
+    }
+
+    fn read_legacy_frames(&mut self, frame_count: usize, value_len: usize) -> Result<Vec<f32>, String> {
+        let mut times = Vec::with_capacity(frame_count);
+        for frame in 0..frame_count {
+            times.push(self.input.read_float()?);
+            self.input.skip(value_len)?;
+            if frame + 1 < frame_count {
+                self.skip_legacy_curve()?;
+            }
+        }
+        Ok(times)
+    }
+
+    fn read_curve_frames(&mut self, frame_count: usize, channels: usize, value_len: usize) -> Result<Vec<f32>, String> {
+        self.input.read_varint(true)?;
+        let mut times = Vec::with_capacity(frame_count);
+        for frame in 0..frame_count {
+            times.push(self.input.read_float()?);
+            self.input.skip(value_len)?;
+            if frame > 0 {
+                self.skip_curve(channels)?;
+            }
+        }
+        Ok(times)
+    }
+
+    fn read_frame_values(&mut self, values: FrameValues) -> Result<Vec<f32>, String> {
+        match values {
+            FrameValues::Floats(n) => self.input.read_floats(n),
+            FrameValues::Bytes(keep, total) => {
+                let mut out = Vec::with_capacity(keep);
+                for i in 0..total {
+                    let value = self.input.read_byte()?;
+                    if i < keep {
+                        out.push(value as f32 / 255.0);
+                    }
+                }
+                Ok(out)
+            }
+            FrameValues::LegacyColor(dark) => {
+                let color = self.input.read_color()?;
+                if dark {
+                    self.input.skip(4)?;
+                }
+                Ok(vec![color.r, color.g, color.b, color.a])
+            }
+        }
+    }
+
+    fn read_legacy_curve(&mut self) -> Result<Curve, String> {
+        Ok(match self.input.read_byte()? {
+            CURVE_STEPPED => Curve::Stepped,
+            CURVE_BEZIER => {
+                let points = self.input.read_floats(4)?;
+                Curve::Bezier(vec![[points[0], points[1], points[2], points[3]]])
+            }
+            _ => Curve::Linear,
+        })
+    }
+
+    fn read_curve(&mut self, channels: usize, start: (f32, &[f32]), end: (f32, &[f32])) -> Result<Curve, String> {
+        Ok(match self.input.read_byte()? {
+            CURVE_STEPPED => Curve::Stepped,
+            CURVE_BEZIER => Curve::normalized(&self.input.read_floats(channels * 4)?, start, end),
+            _ => Curve::Linear,
+        })
+    }
+
+    fn read_legacy_keyframes(&mut self, frame_count: usize, values: FrameValues) -> Result<Vec<Keyframe>, String> {
+        let mut frames = Vec::with_capacity(frame_count);
+        for frame in 0..frame_count {
+            let time = self.input.read_float()?;
+            let values = self.read_frame_values(values)?;
+            let curve = if frame + 1 < frame_count {
+                self.read_legacy_curve()?
+            } else {
+                Curve::Linear
+            };
+            frames.push(Keyframe { time, values, curve });
+        }
+        Ok(frames)
+    }
+
+    fn read_curve_keyframes(
+        &mut self,
+        frame_count: usize,
+        channels: usize,
+        values: FrameValues,
+    ) -> Result<Vec<Keyframe>, String> {
+        self.input.read_varint(true)?;
+        let mut frames: Vec<Keyframe> = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            let time = self.input.read_float()?;
+            let values = self.read_frame_values(values)?;
+            if let Some(previous) = frames.last_mut() {
+                previous.curve = self.read_curve(channels, (previous.time, &previous.values), (time, &values))?;
+            }
+            frames.push(Keyframe {
+                time,
+                values,
+                curve: Curve::Linear,
+            });
+        }
+        Ok(frames)
+    }
+
+    fn read_times(&mut self, frame_count: usize, value_len: usize) -> Result<Vec<f32>, String> {
+        let mut times = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            times.push(self.input.read_float()?);
+            self.input.skip(value_len)?;
+        }
+        Ok(times)
+    }
+
+    fn read_animation(&mut self, name: String, skeleton: &SkeletonData) -> Result<Animation, String> {
+        let modern = self.at_least(RuntimeVersion::V40);
+        let mut timelines: Vec<Vec<f32>> = Vec::new();
+        let mut keyed = Vec::new();
+        if modern {
+            self.input.read_varint(true)?;
+        }
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            let slot = self.input.read_count()?;
+            let nn = self.input.read_count()?;
+            for _ in 0..nn {
+                let timeline_type = self.input.read_byte()?;
+                let frame_count = self.input.read_count()?;
+                let (property, frames) = match (timeline_type, modern) {
+                    (0, _) => {
+                        let mut frames = Vec::with_capacity(frame_count);
+                        for _ in 0..frame_count {
+                            let time = self.input.read_float()?;
+                            frames.push((time, self.read_name()?));
+                        }
+                        timelines.push(frames.iter().map(|(time, _)| *time).collect());
+                        keyed.push(Timeline::Attachment { slot, frames });
+                        continue;
+                    }
+                    (1 | 2, false) => {
+                        let values = FrameValues::LegacyColor(timeline_type == 2);
+                        (ColorProperty::Rgba, self.read_legacy_keyframes(frame_count, values)?)
+                    }
+                    (1..=5, true) => {
+                        let index = timeline_type as usize - 1;
+                        let channels = [4, 3, 7, 6, 1][index];
+                        let keep = [4, 3, 4, 3, 1][index];
+                        let property = [
+                            ColorProperty::Rgba,
+                            ColorProperty::Rgb,
+                            ColorProperty::Rgba,
+                            ColorProperty::Rgb,
+                            ColorProperty::Alpha,
+                        ][index];
+                        let values = FrameValues::Bytes(keep, channels);
+                        (property, self.read_curve_keyframes(frame_count, channels, values)?)
+                    }
+                    (t, _) => return Err(format!("Unknown slot timeline type: {}", t)),
+                };
+                timelines.push(frame_times(&frames));
+                keyed.push(Timeline::Color { slot, property, frames });
+            }
+        }
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            let bone = self.input.read_count()?;
+            let nn = self.input.read_count()?;
+            for _ in 0..nn {
+                let timeline_type = self.input.read_byte()?;
+                let frame_count = self.input.read_count()?;
+                let (property, frames) = if !modern {
+                    let channels = if timeline_type == 0 { 1 } else { 2 };
+                    let property = match timeline_type {
+                        0 => Some(BoneProperty::Rotate),
+                        1 => Some(BoneProperty::Translate),
+                        2 => Some(BoneProperty::Scale),
+                        3 => Some(BoneProperty::Shear),
+                        _ => None,
+                    };
+                    (property, self.read_legacy_keyframes(frame_count, FrameValues::Floats(channels))?)
+                } else if timeline_type == 10 && self.at_least(RuntimeVersion::V42) {
+                    timelines.push(self.read_times(frame_count, 1)?);
+                    continue;
+                } else {
+                    let (property, channels) = match timeline_type {
+                        0 => (BoneProperty::Rotate, 1),
+                        1 => (BoneProperty::Translate, 2),
+                        2 => (BoneProperty::TranslateX, 1),
+                        3 => (BoneProperty::TranslateY, 1),
+                        4 => (BoneProperty::Scale, 2),
+                        5 => (BoneProperty::ScaleX, 1),
+                        6 => (BoneProperty::ScaleY, 1),
+                        7 => (BoneProperty::Shear, 2),
+                        8 => (BoneProperty::ShearX, 1),
+                        9 => (BoneProperty::ShearY, 1),
+                        t => return Err(format!("Unknown bone timeline type: {}", t)),
+                    };
+                    (Some(property), self.read_curve_keyframes(frame_count, channels, FrameValues::Floats(channels))?)
+                };
+                timelines.push(frame_times(&frames));
+                if let Some(property) = property {
+                    keyed.push(Timeline::Bone { bone, property, frames });
+                }
+            }
+        }
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            self.input.read_varint(true)?;
+            let frame_count = self.input.read_count()?;
+            let times = self.read_ik_frames(frame_count)?;
+            timelines.push(times);
+        }
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            self.input.read_varint(true)?;
+            let frame_count = self.input.read_count()?;
+            let times = if modern {
+                self.read_curve_frames(frame_count, 6, 6 * 4)?
+            } else {
+                self.read_legacy_frames(frame_count, 4 * 4)?
+            };
+            timelines.push(times);
+        }
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            self.input.read_varint(true)?;
+            let nn = self.input.read_count()?;
+            for _ in 0..nn {
+                let timeline_type = self.input.read_byte()?;
+                let frame_count = self.input.read_count()?;
+                let channels = match (timeline_type, modern) {
+                    (2, true) => 3,
+                    (2, false) => 2,
+                    _ => 1,
+                };
+                let times = if modern {
+                    self.read_curve_frames(frame_count, channels, channels * 4)?
+                } else {
+                    self.read_legacy_frames(frame_count, channels * 4)?
+                };
+                timelines.push(times);
+            }
+        }
+
+        if self.at_least(RuntimeVersion::V42) {
+            let n = self.input.read_count()?;
+            for _ in 0..n {
+                self.input.read_varint(true)?;
+                let nn = self.input.read_count()?;
+                for _ in 0..nn {
+                    let timeline_type = self.input.read_byte()?;
+                    let frame_count = self.input.read_count()?;
+                    let times = if timeline_type == 8 {
+                        self.read_times(frame_count, 0)?
+                    } else {
+                        self.read_curve_frames(frame_count, 1, 4)?
+                    };
+                    timelines.push(times);
+                }
+            }
+        }
+
+        let n = self.input.read_count()?;
+        for _ in 0..n {
+            let skin = self.input.read_count()?;
+            let nn = self.input.read_count()?;
+            for _ in 0..nn {
+                let slot = self.input.read_count()?;
+                let nnn = self.input.read_count()?;
+                for _ in 0..nnn {
+                    let attachment = self.read_name()?.unwrap_or_default();
+                    let timeline_type = if self.at_least(RuntimeVersion::V41) {
+                        self.input.read_byte()?
+                    } else {
+                        0
+                    };
+                    let frame_count = self.input.read_count()?;
+                    if timeline_type == 1 {
+                        timelines.push(self.read_times(frame_count, 8)?);
+                        continue;
+                    }
+                    let setup = match skeleton
+                        .skins
+                        .get(skin)
+                        .and_then(|s| s.attachments.get(&(slot, attachment.clone())))
+                    {
+                        Some(Attachment::Mesh(mesh)) => Some(&mesh.vertices),
+                        Some(Attachment::Clipping(clipping)) => Some(&clipping.vertices),
+                        _ => None,
+                    };
+                    let frames = self.read_deform_frames(frame_count, setup)?;
+                    timelines.push(frame_times(&frames));
+                    if setup.is_some() {
+                        keyed.push(Timeline::Deform {
+                            slot,
+                            attachment,
+                            frames,
+                        });
+                    }
+                }
+            }
+        }
+
+        let draw_order_count = self.input.read_count()?;
+        let mut frames = Vec::with_capacity(draw_order_count);
+        for _ in 0..draw_order_count {
+            let time = self.input.read_float()?;
+            let offset_count = self.input.read_count()?;
+            let mut offsets = Vec::with_capacity(offset_count);
+            for _ in 0..offset_count {
+                let slot = self.input.read_count()?;
+                offsets.push((slot, self.input.read_varint(true)?));
+            }
+            frames.push((time, draw_order_from_offsets(skeleton.slots.len(), &offsets)));
+        }
+        timelines.push(frames.iter().map(|(time, _)| *time).collect());
+        if !frames.is_empty() {
+            keyed.push(Timeline::DrawOrder { frames });
+        }
+
+        let event_count = self.input.read_count()?;
+        let mut times = Vec::with_capacity(event_count);
+        for _ in 0..event_count {
+            times.push(self.input.read_float()?);
+            let event = self.input.read_count()?;
+            self.input.read_varint(false)?;
+            self.input.skip(4)?;
+            if self.at_least(RuntimeVersion::V42) || self.input.read_bool()? {
+                self.input.read_string()?;
+            }
+            let has_audio = skeleton
+                .events
+                .get(event)
+                .map(|e| e.audio_path.is_some())
+                .unwrap_or(false);
+            if has_audio {
+                self.input.skip(8)?;
+            }
+        }
+        timelines.push(times);
+
+        Ok(Animation::new(name, &timelines, keyed))
+    }
+
+    fn read_ik_frames(&mut self, frame_count: usize) -> Result<Vec<f32>, String> {
+        let v = self.version;
+        if v < RuntimeVersion::V40 {
+            let floats = if v >= RuntimeVersion::V38 { 2 } else { 1 };
+            let flags = if v >= RuntimeVersion::V37 { 2 } else { 0 };
+            return self.read_legacy_frames(frame_count, floats * 4 + 1 + flags);
+        }
+        self.input.read_varint(true)?;
+        let mut times = Vec::with_capacity(frame_count);
+        for frame in 0..frame_count {
+            if v >= RuntimeVersion::V42 {
+                let flags = self.input.read_byte()?;
+                times.push(self.input.read_float()?);
+                if flags & 1 != 0 && flags & 2 != 0 {
+                    self.input.skip(4)?;
+                }
+                self.skip_flagged_floats(flags, &[4])?;
+                if frame > 0 && flags & 64 == 0 && flags & 128 != 0 {
+                    self.input.skip(2 * 4 * 4)?;
+                }
+            } else {
+                times.push(self.input.read_float()?);
+                self.input.skip(2 * 4)?;
+                if frame > 0 {
+                    self.skip_curve(2)?;
+                }
+                self.input.skip(3)?;
+            }
+        }
+        Ok(times)
+    }
+
+    fn read_deform_frames(&mut self, frame_count: usize, setup: Option<&Vertices>) -> Result<Vec<Keyframe>, String> {
+        let modern = self.at_least(RuntimeVersion::V40);
+        if modern {
+            self.input.read_varint(true)?;
+        }
+        let weighted = setup.is_some_and(|v| v.is_weighted());
+        let deform_len = match setup {
+            Some(v) if weighted => v.values.len() / 3 * 2,
+            Some(v) => v.values.len(),
+            None => 0,
+        };
+        let mut frames: Vec<Keyframe> = Vec::with_capacity(frame_count);
+        for frame in 0..frame_count {
+            let time = self.input.read_float()?;
+            if modern && let Some(previous) = frames.last_mut() {
+                previous.curve = self.read_curve(1, (previous.time, &[0.0]), (time, &[1.0]))?;
+            }
+            let mut values = vec![0.0; deform_len];
+            let end = self.input.read_count()?;
+            if end > 0 {
+                let start = self.input.read_count()?;
+                for (i, value) in self.input.read_floats(end)?.into_iter().enumerate() {
+                    if let Some(slot) = values.get_mut(start + i) {
+                        *slot = value;
+                    }
+                }
+            }
+            if !weighted && let Some(setup) = setup {
+                for (value, base) in values.iter_mut().zip(&setup.values) {
+                    *value += base;
+                }
+            }
+            let curve = if !modern && frame + 1 < frame_count {
+                self.read_legacy_curve()?
+            } else {
+                Curve::Linear
+            };
+            frames.push(Keyframe { time, values, curve });
+        }
+        Ok(frames)
+    }
+}
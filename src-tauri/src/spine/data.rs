@@ -0,0 +1,443 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) enum TransformMode {
+    #[default]
+    Normal,
+    OnlyTranslation,
+    NoRotationOrReflection,
+    NoScale,
+    NoScaleOrReflection,
+}
+
+impl TransformMode {
+    pub(crate) fn from_index(index: i32) -> Self {
+        match index {
+            1 => TransformMode::OnlyTranslation,
+            2 => TransformMode::NoRotationOrReflection,
+            3 => TransformMode::NoScale,
+            4 => TransformMode::NoScaleOrReflection,
+            _ => TransformMode::Normal,
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "onlytranslation" => TransformMode::OnlyTranslation,
+            "norotationorreflection" => TransformMode::NoRotationOrReflection,
+            "noscale" => TransformMode::NoScale,
+            "noscaleorreflection" => TransformMode::NoScaleOrReflection,
+            _ => TransformMode::Normal,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) enum BlendMode {
+    #[default]
+    Normal,
+    Additive,
+    Multiply,
+    Screen,
+}
+
+impl BlendMode {
+    pub(crate) fn from_index(index: i32) -> Self {
+        match index {
+            1 => BlendMode::Additive,
+            2 => BlendMode::Multiply,
+            3 => BlendMode::Screen,
+            _ => BlendMode::Normal,
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "additive" => BlendMode::Additive,
+            "multiply" => BlendMode::Multiply,
+            "screen" => BlendMode::Screen,
+            _ => BlendMode::Normal,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Color {
+    pub(crate) r: f32,
+    pub(crate) g: f32,
+    pub(crate) b: f32,
+    pub(crate) a: f32,
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::WHITE
+    }
+}
+
+impl Color {
+    pub(crate) const WHITE: Color = Color {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    };
+
+    pub(crate) fn from_rgba8888(value: u32) -> Self {
+        let channel = |shift: u32| ((value >> shift) & 0xFF) as f32 / 255.0;
+        Color {
+            r: channel(24),
+            g: channel(16),
+            b: channel(8),
+            a: channel(0),
+        }
+    }
+
+    pub(crate) fn from_hex(hex: &str) -> Self {
+        let channel = |i: usize| {
+            hex.get(i * 2..i * 2 + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .map(|c| c as f32 / 255.0)
+                .unwrap_or(1.0)
+        };
+        Color {
+            r: channel(0),
+            g: channel(1),
+            b: channel(2),
+            a: channel(3),
+        }
+    }
+
+    pub(crate) fn mul(self, other: Color) -> Self {
+        Color {
+            r: self.r * other.r,
+            g: self.g * other.g,
+            b: self.b * other.b,
+            a: self.a * other.a,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct BoneData {
+    pub(crate) name: String,
+    pub(crate) parent: Option<usize>,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) rotation: f32,
+    pub(crate) scale_x: f32,
+    pub(crate) scale_y: f32,
+    pub(crate) shear_x: f32,
+    pub(crate) shear_y: f32,
+    pub(crate) transform_mode: TransformMode,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SlotData {
+    pub(crate) name: String,
+    pub(crate) bone: usize,
+    pub(crate) attachment: Option<String>,
+    pub(crate) color: Color,
+    pub(crate) blend: BlendMode,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Vertices {
+    pub(crate) count: usize,
+    pub(crate) bones: Vec<usize>,
+    pub(crate) values: Vec<f32>,
+}
+
+impl Vertices {
+    pub(crate) fn is_weighted(&self) -> bool {
+        !self.bones.is_empty()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RegionAttachment {
+    pub(crate) path: String,
+    pub(crate) color: Color,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) rotation: f32,
+    pub(crate) scale_x: f32,
+    pub(crate) scale_y: f32,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MeshAttachment {
+    pub(crate) path: String,
+    pub(crate) color: Color,
+    pub(crate) vertices: Vertices,
+    pub(crate) uvs: Vec<f32>,
+    pub(crate) triangles: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ClippingAttachment {
+    pub(crate) end_slot: Option<usize>,
+    pub(crate) vertices: Vertices,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Attachment {
+    Region(RegionAttachment),
+    Mesh(MeshAttachment),
+    Clipping(ClippingAttachment),
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum LinkedSkin {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct LinkedMesh {
+    pub(crate) skin: usize,
+    pub(crate) slot: usize,
+    pub(crate) name: String,
+    pub(crate) parent_skin: Option<LinkedSkin>,
+    pub(crate) parent: String,
+    pub(crate) path: String,
+    pub(crate) color: Color,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Skin {
+    pub(crate) name: String,
+    pub(crate) attachments: HashMap<(usize, String), Attachment>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventData {
+    pub(crate) name: String,
+    pub(crate) audio_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) enum Curve {
+    #[default]
+    Linear,
+    Stepped,
+    Bezier(Vec<[f32; 4]>),
+}
+
+impl Curve {
+    pub(crate) fn normalized(points: &[f32], start: (f32, &[f32]), end: (f32, &[f32])) -> Self {
+        let span = end.0 - start.0;
+        let channels = points
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(i, p)| {
+                let from = start.1.get(i).copied().unwrap_or(0.0);
+                let to = end.1.get(i).copied().unwrap_or(1.0);
+                let time = |t: f32| if span > 0.0 { (t - start.0) / span } else { 0.0 };
+                let value = |v: f32| if to != from { (v - from) / (to - from) } else { 0.0 };
+                [time(p[0]), value(p[1]), time(p[2]), value(p[3])]
+            })
+            .collect();
+        Curve::Bezier(channels)
+    }
+
+    pub(crate) fn percent(&self, channel: usize, x: f32) -> f32 {
+        let points = match self {
+            Curve::Linear => return x,
+            Curve::Stepped => return 0.0,
+            Curve::Bezier(channels) => match channels.get(channel).or(channels.last()) {
+                Some(points) => points,
+                None => return x,
+            },
+        };
+        let [cx1, cy1, cx2, cy2] = *points;
+        let bezier = |t: f32, p1: f32, p2: f32| {
+            let u = 1.0 - t;
+            3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+        };
+        let (mut low, mut high) = (0.0f32, 1.0f32);
+        for _ in 0..24 {
+            let mid = (low + high) / 2.0;
+            if bezier(mid, cx1, cx2) < x {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        bezier((low + high) / 2.0, cy1, cy2)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Keyframe {
+    pub(crate) time: f32,
+    pub(crate) values: Vec<f32>,
+    pub(crate) curve: Curve,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum BoneProperty {
+    Rotate,
+    Translate,
+    TranslateX,
+    TranslateY,
+    Scale,
+    ScaleX,
+    ScaleY,
+    Shear,
+    ShearX,
+    ShearY,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ColorProperty {
+    Rgba,
+    Rgb,
+    Alpha,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Timeline {
+    Bone {
+        bone: usize,
+        property: BoneProperty,
+        frames: Vec<Keyframe>,
+    },
+    Color {
+        slot: usize,
+        property: ColorProperty,
+        frames: Vec<Keyframe>,
+    },
+    Attachment {
+        slot: usize,
+        frames: Vec<(f32, Option<String>)>,
+    },
+    Deform {
+        slot: usize,
+        attachment: String,
+        frames: Vec<Keyframe>,
+    },
+    DrawOrder {
+        frames: Vec<(f32, Option<Vec<usize>>)>,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Animation {
+    pub(crate) name: String,
+    pub(crate) duration: f32,
+    pub(crate) timelines: Vec<Timeline>,
+}
+
+impl Animation {
+    pub(crate) fn new(name: String, timeline_times: &[Vec<f32>], timelines: Vec<Timeline>) -> Self {
+        let duration = timeline_times
+            .iter()
+            .filter_map(|times| times.last().copied())
+            .fold(0.0, f32::max);
+        Self {
+            name,
+            duration,
+            timelines,
+        }
+    }
+}
+
+pub(crate) fn sequence_path(path: &str, index: i32, digits: usize) -> String {
+    format!("{}{:0width$}", path, index, width = digits)
+}
+
+pub(crate) fn draw_order_from_offsets(slot_count: usize, offsets: &[(usize, i32)]) -> Option<Vec<usize>> {
+    let mut draw_order: Vec<Option<usize>> = vec![None; slot_count];
+    let mut unchanged = Vec::with_capacity(slot_count);
+    let mut original = 0;
+    for &(slot, offset) in offsets {
+        while original < slot.min(slot_count) {
+            unchanged.push(original);
+            original += 1;
+        }
+        let target = (original as i64 + offset as i64).clamp(0, slot_count as i64 - 1) as usize;
+        if original < slot_count {
+            draw_order[target] = Some(original);
+            original += 1;
+        }
+    }
+    while original < slot_count {
+        unchanged.push(original);
+        original += 1;
+    }
+    for entry in draw_order.iter_mut().rev() {
+        if entry.is_none() {
+            *entry = unchanged.pop();
+        }
+    }
+    draw_order.into_iter().collect()
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SkeletonData {
+    pub(crate) version: String,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    pub(crate) fps: Option<f32>,
+    pub(crate) images: Option<String>,
+    pub(crate) bones: Vec<BoneData>,
+    pub(crate) slots: Vec<SlotData>,
+    pub(crate) skins: Vec<Skin>,
+    pub(crate) events: Vec<EventData>,
+    pub(crate) animations: Vec<Animation>,
+    pub(crate) region_paths: HashSet<String>,
+    pub(crate) constraints: usize,
+}
+
+impl SkeletonData {
+    pub(crate) fn find_skin(&self, name: &str) -> Option<usize> {
+        self.skins.iter().position(|s| s.name == name)
+    }
+
+    pub(crate) fn default_skin(&self) -> Option<usize> {
+        self.find_skin("default")
+            .or_else(|| (!self.skins.is_empty()).then_some(0))
+    }
+
+    pub(crate) fn attachment(&self, skin: Option<usize>, slot: usize, name: &str) -> Option<&Attachment> {
+        let key = (slot, name.to_string());
+        skin.and_then(|i| self.skins.get(i))
+            .and_then(|s| s.attachments.get(&key))
+            .or_else(|| {
+                self.default_skin()
+                    .and_then(|i| self.skins[i].attachments.get(&key))
+            })
+    }
+
+    pub(crate) fn resolve_linked_meshes(&mut self, linked_meshes: Vec<LinkedMesh>) -> Result<(), String> {
+        for linked in linked_meshes {
+            let parent_skin = match &linked.parent_skin {
+                Some(LinkedSkin::Index(i)) => Some(*i),
+                Some(LinkedSkin::Name(name)) => Some(
+                    self.find_skin(name)
+                        .ok_or_else(|| format!("Skin not found: {}", name))?,
+                ),
+                None => None,
+            };
+            let mesh = match self.attachment(parent_skin, linked.slot, &linked.parent) {
+                Some(Attachment::Mesh(parent)) => MeshAttachment {
+                    path: linked.path,
+                    color: linked.color,
+                    ..parent.clone()
+                },
+                _ => return Err(format!("Parent mesh not found: {}", linked.parent)),
+            };
+            if let Some(skin) = self.skins.get_mut(linked.skin) {
+                skin.attachments
+                    .insert((linked.slot, linked.name), Attachment::Mesh(mesh));
+            }
+        }
+        Ok(())
+    }
+}
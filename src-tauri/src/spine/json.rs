@@ -0,0 +1,463 @@
+use serde_json::Value;
+
+use super::RuntimeVersion;
+use super::data::*;
+
+fn num(value: &Value, key: &str, default: f32) -> f32 {
+    value
+        .get(key)
+        .and_then(Value::as_f64)
+        .map(|v| v as f32)
+        .unwrap_or(default)
+}
+
+fn text<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn entries<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn find_index<T>(items: &[T], name: &str, item_name: impl Fn(&T) -> &str) -> Result<usize, String> {
+    items
+        .iter()
+        .position(|item| item_name(item) == name)
+        .ok_or_else(|| format!("Reference not found: {}", name))
+}
+
+fn read_vertices(value: &Value, vertex_len: usize) -> Vertices {
+    let values: Vec<f32> = entries(value, "vertices")
+        .iter()
+        .map(|v| v.as_f64().unwrap_or(0.0) as f32)
+        .collect();
+    if values.len() == vertex_len {
+        return Vertices {
+            count: vertex_len / 2,
+            bones: Vec::new(),
+            values,
+        };
+    }
+    let mut bones = Vec::new();
+    let mut weights = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let bone_count = values[i] as usize;
+        bones.push(bone_count);
+        i += 1;
+        for _ in 0..bone_count {
+            if i + 4 > values.len() {
+                break;
+            }
+            bones.push(values[i] as usize);
+            weights.extend_from_slice(&values[i + 1..i + 4]);
+            i += 4;
+        }
+    }
+    Vertices {
+        count: vertex_len / 2,
+        bones,
+        values: weights,
+    }
+}
+
+fn color(value: &Value, key: &str) -> Color {
+    text(value, key).map(Color::from_hex).unwrap_or_default()
+}
+
+fn floats(value: &Value, key: &str) -> Vec<f32> {
+    entries(value, key)
+        .iter()
+        .map(|v| v.as_f64().unwrap_or(0.0) as f32)
+        .collect()
+}
+
+fn region_path(value: &Value, placeholder: &str) -> String {
+    let path = text(value, "path").or_else(|| text(value, "name")).unwrap_or(placeholder);
+    match value.get("sequence") {
+        Some(sequence) => sequence_path(
+            path,
+            (num(sequence, "start", 1.0) + num(sequence, "setup", 0.0)) as i32,
+            num(sequence, "digits", 0.0) as usize,
+        ),
+        None => path.to_string(),
+    }
+}
+
+fn region_paths(value: &Value, placeholder: &str) -> Vec<String> {
+    let path = text(value, "path").or_else(|| text(value, "name")).unwrap_or(placeholder);
+    match value.get("sequence") {
+        Some(sequence) => {
+            let start = num(sequence, "start", 1.0) as i32;
+            let count = num(sequence, "count", 0.0) as i32;
+            let digits = num(sequence, "digits", 0.0) as usize;
+            (start..start + count).map(|index| sequence_path(path, index, digits)).collect()
+        }
+        None => vec![path.to_string()],
+    }
+}
+
+fn max_key_time(value: &Value) -> f32 {
+    match value {
+        Value::Array(items) => items.iter().map(max_key_time).fold(0.0, f32::max),
+        Value::Object(map) => {
+            let own = map.get("time").and_then(Value::as_f64).unwrap_or(0.0) as f32;
+            map.values().map(max_key_time).fold(own, f32::max)
+        }
+        _ => 0.0,
+    }
+}
+
+pub(crate) fn read_skeleton_data(root: &Value, version: &str) -> Result<SkeletonData, String> {
+    RuntimeVersion::parse(version).ok_or_else(|| format!("Unsupported Spine version: {}", version))?;
+    let header = root.get("skeleton").unwrap_or(&Value::Null);
+    let mut skeleton = SkeletonData {
+        version: version.to_string(),
+        x: num(header, "x", 0.0),
+        y: num(header, "y", 0.0),
+        width: num(header, "width", 0.0),
+        height: num(header, "height", 0.0),
+        fps: header.get("fps").and_then(Value::as_f64).map(|v| v as f32),
+        images: text(header, "images").map(str::to_string),
+        ..Default::default()
+    };
+
+    for bone in entries(root, "bones") {
+        let parent = match text(bone, "parent") {
+            Some(name) => Some(find_index(&skeleton.bones, name, |b| &b.name)?),
+            None => None,
+        };
+        let transform_mode = text(bone, "inherit")
+            .or_else(|| text(bone, "transform"))
+            .map(TransformMode::from_name)
+            .unwrap_or_default();
+        skeleton.bones.push(BoneData {
+            name: text(bone, "name").unwrap_or_default().to_string(),
+            parent,
+            x: num(bone, "x", 0.0),
+            y: num(bone, "y", 0.0),
+            rotation: num(bone, "rotation", 0.0),
+            scale_x: num(bone, "scaleX", 1.0),
+            scale_y: num(bone, "scaleY", 1.0),
+            shear_x: num(bone, "shearX", 0.0),
+            shear_y: num(bone, "shearY", 0.0),
+            transform_mode,
+        });
+    }
+
+    for slot in entries(root, "slots") {
+        let bone = find_index(&skeleton.bones, text(slot, "bone").unwrap_or_default(), |b| &b.name)?;
+        skeleton.slots.push(SlotData {
+            name: text(slot, "name").unwrap_or_default().to_string(),
+            bone,
+            attachment: text(slot, "attachment").map(str::to_string),
+            color: color(slot, "color"),
+            blend: text(slot, "blend").map(BlendMode::from_name).unwrap_or_default(),
+        });
+    }
+
+    skeleton.constraints = ["ik", "transform", "path", "physics"]
+        .iter()
+        .map(|key| entries(root, key).len())
+        .sum();
+
+    let mut linked_meshes = Vec::new();
+    match root.get("skins") {
+        Some(Value::Array(skins)) => {
+            for skin in skins {
+                let name = text(skin, "name").unwrap_or_default().to_string();
+                let attachments = skin.get("attachments").unwrap_or(&Value::Null);
+                read_skin(&mut skeleton, name, attachments, &mut linked_meshes)?;
+            }
+        }
+        Some(Value::Object(skins)) => {
+            for (name, attachments) in skins {
+                read_skin(&mut skeleton, name.clone(), attachments, &mut linked_meshes)?;
+            }
+        }
+        _ => {}
+    }
+    skeleton.resolve_linked_meshes(linked_meshes)?;
+
+    if let Some(events) = root.get("events").and_then(Value::as_object) {
+        for (name, event) in events {
+            skeleton.events.push(EventData {
+                name: name.clone(),
+                audio_path: text(event, "audio").map(str::to_string),
+            });
+        }
+    }
+
+    let modern = RuntimeVersion::parse(version).is_some_and(|v| v >= RuntimeVersion::V40);
+    if let Some(animations) = root.get("animations").and_then(Value::as_object) {
+        for (name, animation) in animations {
+            let timelines = read_timelines(&skeleton, animation, modern);
+            skeleton.animations.push(Animation {
+                name: name.clone(),
+                duration: max_key_time(animation),
+                timelines,
+            });
+        }
+    }
+    Ok(skeleton)
+}
+
+fn read_skin(
+    skeleton: &mut SkeletonData,
+    name: String,
+    attachments: &Value,
+    linked_meshes: &mut Vec<LinkedMesh>,
+) -> Result<(), String> {
+    let skin_index = skeleton.skins.len();
+    let mut skin = Skin {
+        name,
+        ..Default::default()
+    };
+    for (slot_name, slot_attachments) in attachments.as_object().into_iter().flatten() {
+        let slot = find_index(&skeleton.slots, slot_name, |s| &s.name)?;
+        for (placeholder, value) in slot_attachments.as_object().into_iter().flatten() {
+            let attachment_type = text(value, "type").unwrap_or("region");
+            if matches!(attachment_type, "region" | "mesh" | "skinnedmesh" | "linkedmesh") {
+                skeleton.region_paths.extend(region_paths(value, placeholder));
+            }
+            let attachment = match attachment_type {
+                "region" => Attachment::Region(RegionAttachment {
+                    path: region_path(value, placeholder),
+                    color: color(value, "color"),
+                    x: num(value, "x", 0.0),
+                    y: num(value, "y", 0.0),
+                    rotation: num(value, "rotation", 0.0),
+                    scale_x: num(value, "scaleX", 1.0),
+                    scale_y: num(value, "scaleY", 1.0),
+                    width: num(value, "width", 32.0),
+                    height: num(value, "height", 32.0),
+                }),
+                "mesh" | "skinnedmesh" => {
+                    let uvs = floats(value, "uvs");
+                    Attachment::Mesh(MeshAttachment {
+                        path: region_path(value, placeholder),
+                        color: color(value, "color"),
+                        vertices: read_vertices(value, uvs.len()),
+                        uvs,
+                        triangles: floats(value, "triangles").iter().map(|i| *i as usize).collect(),
+                    })
+                }
+                "linkedmesh" => {
+                    linked_meshes.push(LinkedMesh {
+                        skin: skin_index,
+                        slot,
+                        name: placeholder.clone(),
+                        parent_skin: text(value, "skin").map(|s| LinkedSkin::Name(s.to_string())),
+                        parent: text(value, "parent").unwrap_or_default().to_string(),
+                        path: region_path(value, placeholder),
+                        color: color(value, "color"),
+                    });
+                    continue;
+                }
+                "clipping" => Attachment::Clipping(ClippingAttachment {
+                    end_slot: text(value, "end").and_then(|end| find_index(&skeleton.slots, end, |s| &s.name).ok()),
+                    vertices: read_vertices(value, num(value, "vertexCount", 0.0) as usize * 2),
+                }),
+                _ => Attachment::Other,
+            };
+            skin.attachments.insert((slot, placeholder.clone()), attachment);
+        }
+    }
+    skeleton.skins.push(skin);
+    Ok(())
+}
+
+fn read_curve(key: &Value, legacy: bool, start: (f32, &[f32]), end: (f32, &[f32])) -> Curve {
+    match key.get("curve") {
+        Some(Value::String(s)) if s == "stepped" => Curve::Stepped,
+        Some(Value::Number(cx1)) if legacy => Curve::Bezier(vec![[
+            cx1.as_f64().unwrap_or(0.0) as f32,
+            num(key, "c2", 0.0),
+            num(key, "c3", 1.0),
+            num(key, "c4", 1.0),
+        ]]),
+        Some(Value::Array(points)) => {
+            let points: Vec<f32> = points.iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
+            if legacy {
+                points
+                    .get(..4)
+                    .map(|p| Curve::Bezier(vec![[p[0], p[1], p[2], p[3]]]))
+                    .unwrap_or_default()
+            } else {
+                Curve::normalized(&points, start, end)
+            }
+        }
+        _ => Curve::Linear,
+    }
+}
+
+fn read_keyframes(keys: &[Value], legacy: bool, percent: bool, values: impl Fn(&Value) -> Vec<f32>) -> Vec<Keyframe> {
+    let mut frames: Vec<Keyframe> = keys
+        .iter()
+        .map(|key| Keyframe {
+            time: num(key, "time", 0.0),
+            values: values(key),
+            curve: Curve::Linear,
+        })
+        .collect();
+    for i in 1..frames.len() {
+        let (head, tail) = frames.split_at_mut(i);
+        let previous = &mut head[i - 1];
+        let next = &tail[0];
+        previous.curve = if percent {
+            read_curve(&keys[i - 1], legacy, (previous.time, &[0.0]), (next.time, &[1.0]))
+        } else {
+            read_curve(&keys[i - 1], legacy, (previous.time, &previous.values), (next.time, &next.values))
+        };
+    }
+    frames
+}
+
+fn color_values(key: &Value, name: &str, len: usize) -> Vec<f32> {
+    let c = color(key, name);
+    [c.r, c.g, c.b, c.a][..len].to_vec()
+}
+
+fn bone_timeline(name: &str, modern: bool) -> Option<(BoneProperty, &'static [&'static str], f32)> {
+    let value_key: &'static [&'static str] = if modern { &["value"] } else { &["angle"] };
+    Some(match name {
+        "rotate" => (BoneProperty::Rotate, value_key, 0.0),
+        "translate" => (BoneProperty::Translate, &["x", "y"], 0.0),
+        "translatex" => (BoneProperty::TranslateX, &["value"], 0.0),
+        "translatey" => (BoneProperty::TranslateY, &["value"], 0.0),
+        "scale" => (BoneProperty::Scale, &["x", "y"], 1.0),
+        "scalex" => (BoneProperty::ScaleX, &["value"], 1.0),
+        "scaley" => (BoneProperty::ScaleY, &["value"], 1.0),
+        "shear" => (BoneProperty::Shear, &["x", "y"], 0.0),
+        "shearx" => (BoneProperty::ShearX, &["value"], 0.0),
+        "sheary" => (BoneProperty::ShearY, &["value"], 0.0),
+        _ => return None,
+    })
+}
+
+fn read_timelines(skeleton: &SkeletonData, animation: &Value, modern: bool) -> Vec<Timeline> {
+    let legacy = !modern;
+    let mut timelines = Vec::new();
+    for (slot_name, slot_timelines) in animation.get("slots").and_then(Value::as_object).into_iter().flatten() {
+        let Ok(slot) = find_index(&skeleton.slots, slot_name, |s| &s.name) else {
+            continue;
+        };
+        for (timeline_name, keys) in slot_timelines.as_object().into_iter().flatten() {
+            let keys = keys.as_array().map(Vec::as_slice).unwrap_or_default();
+            let (property, key_name) = match timeline_name.as_str() {
+                "attachment" => {
+                    let frames = keys
+                        .iter()
+                        .map(|key| (num(key, "time", 0.0), text(key, "name").map(str::to_string)))
+                        .collect();
+                    timelines.push(Timeline::Attachment { slot, frames });
+                    continue;
+                }
+                "color" | "rgba" => (ColorProperty::Rgba, "color"),
+                "twoColor" | "rgba2" => (ColorProperty::Rgba, "light"),
+                "rgb" => (ColorProperty::Rgb, "color"),
+                "rgb2" => (ColorProperty::Rgb, "light"),
+                "alpha" => (ColorProperty::Alpha, "value"),
+                _ => continue,
+            };
+            let frames = read_keyframes(keys, legacy, false, |key| match property {
+                ColorProperty::Rgba => color_values(key, key_name, 4),
+                ColorProperty::Rgb => color_values(key, key_name, 3),
+                ColorProperty::Alpha => vec![num(key, key_name, 1.0)],
+            });
+            timelines.push(Timeline::Color { slot, property, frames });
+        }
+    }
+
+    for (bone_name, bone_timelines) in animation.get("bones").and_then(Value::as_object).into_iter().flatten() {
+        let Ok(bone) = find_index(&skeleton.bones, bone_name, |b| &b.name) else {
+            continue;
+        };
+        for (timeline_name, keys) in bone_timelines.as_object().into_iter().flatten() {
+            let Some((property, channels, default)) = bone_timeline(timeline_name, modern) else {
+                continue;
+            };
+            let keys = keys.as_array().map(Vec::as_slice).unwrap_or_default();
+            let frames = read_keyframes(keys, legacy, false, |k| channels.iter().map(|c| num(k, c, default)).collect());
+            timelines.push(Timeline::Bone { bone, property, frames });
+        }
+    }
+
+    let deforms = if modern {
+        animation.get("attachments").or_else(|| animation.get("deform"))
+    } else {
+        animation.get("deform").or_else(|| animation.get("ffd"))
+    };
+    for (skin_name, slots) in deforms.and_then(Value::as_object).into_iter().flatten() {
+        let Some(skin) = skeleton.find_skin(skin_name).and_then(|i| skeleton.skins.get(i)) else {
+            continue;
+        };
+        for (slot_name, attachments) in slots.as_object().into_iter().flatten() {
+            let Ok(slot) = find_index(&skeleton.slots, slot_name, |s| &s.name) else {
+                continue;
+            };
+            for (attachment, keys) in attachments.as_object().into_iter().flatten() {
+                let keys = match keys {
+                    Value::Object(map) => map.get("deform"),
+                    keys => Some(keys),
+                };
+                let keys = keys.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+                let setup = match skin.attachments.get(&(slot, attachment.clone())) {
+                    Some(Attachment::Mesh(mesh)) => &mesh.vertices,
+                    Some(Attachment::Clipping(clipping)) => &clipping.vertices,
+                    _ => continue,
+                };
+                let weighted = setup.is_weighted();
+                let deform_len = if weighted { setup.values.len() / 3 * 2 } else { setup.values.len() };
+                let frames = read_keyframes(keys, legacy, true, |key| {
+                    let mut values = vec![0.0; deform_len];
+                    let start = num(key, "offset", 0.0) as usize;
+                    for (i, value) in floats(key, "vertices").into_iter().enumerate() {
+                        if let Some(slot) = values.get_mut(start + i) {
+                            *slot = value;
+                        }
+                    }
+                    if !weighted {
+                        for (value, base) in values.iter_mut().zip(&setup.values) {
+                            *value += base;
+                        }
+                    }
+                    values
+                });
+                timelines.push(Timeline::Deform {
+                    slot,
+                    attachment: attachment.clone(),
+                    frames,
+                });
+            }
+        }
+    }
+
+    let draw_order = animation.get("drawOrder").or_else(|| animation.get("draworder"));
+    let frames: Vec<(f32, Option<Vec<usize>>)> = draw_order
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|key| {
+            let order = key.get("offsets").and_then(Value::as_array).map(|offsets| {
+                let offsets: Vec<(usize, i32)> = offsets
+                    .iter()
+                    .filter_map(|offset| {
+                        let slot = find_index(&skeleton.slots, text(offset, "slot")?, |s| &s.name).ok()?;
+                        Some((slot, num(offset, "offset", 0.0) as i32))
+                    })
+                    .collect();
+                draw_order_from_offsets(skeleton.slots.len(), &offsets)
+            });
+            (num(key, "time", 0.0), order.flatten())
+        })
+        .collect();
+    if !frames.is_empty() {
+        timelines.push(Timeline::DrawOrder { frames });
+    }
+    timelines
+}
@@ -0,0 +1,242 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+mod animation;
+mod binary;
+pub(crate) mod data;
+mod json;
+pub(crate) mod pose;
+pub(crate) mod render;
+
+use binary::BinaryInput;
+use data::SkeletonData;
+use pose::Bounds;
+
+pub(crate) const SUPPORTED_SPINE_VERSIONS: &[&str] = &["3.6", "3.7", "3.8", "4.0", "4.1", "4.2"];
+
+const HEADER_READ_LEN: usize = 64 * 1024;
+const JSON_SNIFF_LEN: usize = 100;
+
+#[derive(Clone, Debug)]
+pub(crate) struct SkeletonHeader {
+    pub(crate) version: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum RuntimeVersion {
+    V36,
+    V37,
+    V38,
+    V40,
+    V41,
+    V42,
+}
+
+impl RuntimeVersion {
+    pub(crate) fn parse(version: &str) -> Option<Self> {
+        match runtime_version(version)? {
+            "3.6" => Some(RuntimeVersion::V36),
+            "3.7" => Some(RuntimeVersion::V37),
+            "3.8" => Some(RuntimeVersion::V38),
+            "4.0" => Some(RuntimeVersion::V40),
+            "4.1" => Some(RuntimeVersion::V41),
+            _ => Some(RuntimeVersion::V42),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct AnimationInfo {
+    pub(crate) name: String,
+    pub(crate) duration: f32,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct SpineMetadata {
+    pub(crate) version: String,
+    pub(crate) animations: Vec<AnimationInfo>,
+    pub(crate) skins: Vec<String>,
+    pub(crate) events: Vec<String>,
+    #[serde(rename = "boneCount")]
+    pub(crate) bone_count: usize,
+    #[serde(rename = "slotCount")]
+    pub(crate) slot_count: usize,
+    pub(crate) fps: Option<f32>,
+    pub(crate) bounds: Option<Bounds>,
+}
+
+fn is_version_string(s: &str) -> bool {
+    let mut parts = s.split('.');
+    let major = parts.next().unwrap_or("");
+    let minor = parts.next().unwrap_or("");
+    s.len() <= 16
+        && major.len() == 1
+        && major.chars().all(|c| c.is_ascii_digit())
+        && !minor.is_empty()
+        && s.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+pub(crate) fn is_json_data(data: &[u8]) -> bool {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let head = &data[..data.len().min(JSON_SNIFF_LEN)];
+    let Some(start) = head.iter().position(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r')) else {
+        return false;
+    };
+    head[start] == b'{' && !head[start + 1..].iter().any(|b| *b < 9 || (*b > 13 && *b < 32))
+}
+
+fn json_spine_version(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    let mut search_from = 0;
+    while let Some(idx) = text[search_from..].find("\"spine\"") {
+        let after_key = search_from + idx + "\"spine\"".len();
+        let rest = text[after_key..].trim_start();
+        if let Some(rest) = rest.strip_prefix(':')
+            && let Some(rest) = rest.trim_start().strip_prefix('"')
+            && let Some(end) = rest.find('"')
+            && is_version_string(&rest[..end])
+        {
+            return Some(rest[..end].to_string());
+        }
+        search_from = after_key;
+    }
+    None
+}
+
+fn binary_spine_version(data: &[u8]) -> Option<String> {
+    let mut input = BinaryInput::new(data);
+    if input.read_string().is_ok()
+        && let Ok(Some(version)) = input.read_string()
+        && is_version_string(&version)
+        && version.starts_with('3')
+    {
+        return Some(version);
+    }
+    let mut input = BinaryInput::new(data);
+    if input.skip(8).is_ok()
+        && let Ok(Some(version)) = input.read_string()
+        && is_version_string(&version)
+    {
+        return Some(version);
+    }
+    None
+}
+
+pub(crate) fn parse_skeleton_header(data: &[u8]) -> Result<SkeletonHeader, String> {
+    if is_json_data(data) {
+        json_spine_version(data)
+            .map(|version| SkeletonHeader { version })
+            .ok_or_else(|| "skeleton.spine version not found in JSON".to_string())
+    } else {
+        binary_spine_version(data)
+            .map(|version| SkeletonHeader { version })
+            .ok_or_else(|| "Valid version not found in binary skeleton header".to_string())
+    }
+}
+
+fn read_head(path: &Path) -> Result<Vec<u8>, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut data = Vec::with_capacity(HEADER_READ_LEN);
+    file.take(HEADER_READ_LEN as u64)
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+pub(crate) fn read_skeleton_header(path: &Path) -> Result<SkeletonHeader, String> {
+    parse_skeleton_header(&read_head(path)?)
+}
+
+fn strip_trailing_commas(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (idx, c) in text.char_indices() {
+        if c == ',' && text[idx + 1..].trim_start().starts_with(['}', ']']) {
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn parse_json(data: &[u8]) -> Result<serde_json::Value, String> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    serde_json::from_slice(data).or_else(|e| {
+        let text = String::from_utf8_lossy(data);
+        serde_json::from_str(&strip_trailing_commas(&text)).map_err(|_| e.to_string())
+    })
+}
+
+pub(crate) fn is_json_skeleton(data: &[u8]) -> bool {
+    parse_json(data).is_ok_and(|root| {
+        root.get("skeleton").is_some_and(serde_json::Value::is_object)
+            && root.get("bones").is_some_and(serde_json::Value::is_array)
+    })
+}
+
+pub(crate) fn is_skeleton_file(path: &Path) -> bool {
+    let Ok(head) = read_head(path) else {
+        return false;
+    };
+    if !is_json_data(&head) {
+        return binary_spine_version(&head).is_some();
+    }
+    if !String::from_utf8_lossy(&head).contains("\"skeleton\"") {
+        return false;
+    }
+    fs::read(path).is_ok_and(|data| is_json_skeleton(&data))
+}
+
+pub(crate) fn runtime_version(version: &str) -> Option<&'static str> {
+    SUPPORTED_SPINE_VERSIONS
+        .iter()
+        .find(|v| version == **v || version.starts_with(&format!("{}.", v)))
+        .copied()
+}
+
+pub(crate) fn parse_skeleton(data: &[u8]) -> Result<SkeletonData, String> {
+    let header = parse_skeleton_header(data)?;
+    if is_json_data(data) {
+        let root = parse_json(data).map_err(|e| format!("Invalid skeleton JSON: {}", e))?;
+        json::read_skeleton_data(&root, &header.version)
+    } else {
+        binary::read_skeleton_data(data, &header.version)
+    }
+}
+
+pub(crate) fn read_skeleton(path: &Path) -> Result<SkeletonData, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read skeleton: {}", e))?;
+    parse_skeleton(&data)
+}
+
+pub(crate) fn skeleton_metadata(skeleton: &SkeletonData) -> SpineMetadata {
+    let bounds = pose::setup_bounds(skeleton).or_else(|| {
+        (skeleton.width > 0.0 && skeleton.height > 0.0).then_some(Bounds {
+            x: skeleton.x,
+            y: skeleton.y,
+            width: skeleton.width,
+            height: skeleton.height,
+        })
+    });
+    SpineMetadata {
+        version: skeleton.version.clone(),
+        animations: skeleton
+            .animations
+            .iter()
+            .map(|a| AnimationInfo {
+                name: a.name.clone(),
+                duration: a.duration,
+            })
+            .collect(),
+        skins: skeleton.skins.iter().map(|s| s.name.clone()).collect(),
+        events: skeleton.events.iter().map(|e| e.name.clone()).collect(),
+        bone_count: skeleton.bones.len(),
+        slot_count: skeleton.slots.len(),
+        fps: skeleton.fps,
+        bounds,
+    }
+}
+
+pub(crate) fn read_metadata(path: &Path) -> Result<SpineMetadata, String> {
+    read_skeleton(path).map(|skeleton| skeleton_metadata(&skeleton))
+}
@@ -0,0 +1,261 @@
+use super::data::*;
+use crate::atlas::AtlasRegion;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct LocalTransform {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) rotation: f32,
+    pub(crate) scale_x: f32,
+    pub(crate) scale_y: f32,
+    pub(crate) shear_x: f32,
+    pub(crate) shear_y: f32,
+}
+
+impl LocalTransform {
+    pub(crate) fn setup(bone: &BoneData) -> Self {
+        Self {
+            x: bone.x,
+            y: bone.y,
+            rotation: bone.rotation,
+            scale_x: bone.scale_x,
+            scale_y: bone.scale_y,
+            shear_x: bone.shear_x,
+            shear_y: bone.shear_y,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WorldTransform {
+    pub(crate) a: f32,
+    pub(crate) b: f32,
+    pub(crate) c: f32,
+    pub(crate) d: f32,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+}
+
+impl WorldTransform {
+    pub(crate) const IDENTITY: WorldTransform = WorldTransform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        x: 0.0,
+        y: 0.0,
+    };
+
+    pub(crate) fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (x * self.a + y * self.b + self.x, x * self.c + y * self.d + self.y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Bounds {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+}
+
+fn sin_deg(degrees: f32) -> f32 {
+    degrees.to_radians().sin()
+}
+
+fn cos_deg(degrees: f32) -> f32 {
+    degrees.to_radians().cos()
+}
+
+fn world_transform(mode: TransformMode, local: &LocalTransform, parent: Option<&WorldTransform>) -> WorldTransform {
+    let l = local;
+    let rotation_y = l.rotation + 90.0 + l.shear_y;
+    let la = cos_deg(l.rotation + l.shear_x) * l.scale_x;
+    let lb = cos_deg(rotation_y) * l.scale_y;
+    let lc = sin_deg(l.rotation + l.shear_x) * l.scale_x;
+    let ld = sin_deg(rotation_y) * l.scale_y;
+    let Some(p) = parent else {
+        return WorldTransform {
+            a: la,
+            b: lb,
+            c: lc,
+            d: ld,
+            x: l.x,
+            y: l.y,
+        };
+    };
+    let (x, y) = p.apply(l.x, l.y);
+    let (pa, pb, pc, pd) = (p.a, p.b, p.c, p.d);
+    let (a, b, c, d) = match mode {
+        TransformMode::Normal => (pa * la + pb * lc, pa * lb + pb * ld, pc * la + pd * lc, pc * lb + pd * ld),
+        TransformMode::OnlyTranslation => (la, lb, lc, ld),
+        TransformMode::NoRotationOrReflection => {
+            let s = pa * pa + pc * pc;
+            let (pa, pb, pc, pd, prx) = if s > 0.0001 {
+                let s = (pa * pd - pb * pc).abs() / s;
+                (pa, -pc * s, pc, pa * s, pc.atan2(pa).to_degrees())
+            } else {
+                (0.0, pb, 0.0, pd, 90.0 - pd.atan2(pb).to_degrees())
+            };
+            let rx = l.rotation + l.shear_x - prx;
+            let ry = l.rotation + l.shear_y - prx + 90.0;
+            let la = cos_deg(rx) * l.scale_x;
+            let lb = cos_deg(ry) * l.scale_y;
+            let lc = sin_deg(rx) * l.scale_x;
+            let ld = sin_deg(ry) * l.scale_y;
+            (pa * la + pb * lc, pa * lb + pb * ld, pc * la + pd * lc, pc * lb + pd * ld)
+        }
+        TransformMode::NoScale | TransformMode::NoScaleOrReflection => {
+            let cos = cos_deg(l.rotation);
+            let sin = sin_deg(l.rotation);
+            let mut za = pa * cos + pb * sin;
+            let mut zc = pc * cos + pd * sin;
+            let mut s = (za * za + zc * zc).sqrt();
+            if s > 0.00001 {
+                s = 1.0 / s;
+            }
+            za *= s;
+            zc *= s;
+            s = (za * za + zc * zc).sqrt();
+            if mode == TransformMode::NoScale && pa * pd - pb * pc < 0.0 {
+                s = -s;
+            }
+            let r = std::f32::consts::FRAC_PI_2 + zc.atan2(za);
+            let zb = r.cos() * s;
+            let zd = r.sin() * s;
+            let la = cos_deg(l.shear_x) * l.scale_x;
+            let lb = cos_deg(90.0 + l.shear_y) * l.scale_y;
+            let lc = sin_deg(l.shear_x) * l.scale_x;
+            let ld = sin_deg(90.0 + l.shear_y) * l.scale_y;
+            (za * la + zb * lc, za * lb + zb * ld, zc * la + zd * lc, zc * lb + zd * ld)
+        }
+    };
+    WorldTransform { a, b, c, d, x, y }
+}
+
+pub(crate) fn world_transforms(skeleton: &SkeletonData, locals: &[LocalTransform]) -> Vec<WorldTransform> {
+    let mut world: Vec<WorldTransform> = Vec::with_capacity(skeleton.bones.len());
+    for (i, bone) in skeleton.bones.iter().enumerate() {
+        let parent = bone.parent.and_then(|p| world.get(p)).copied();
+        let local = locals.get(i).copied().unwrap_or_else(|| LocalTransform::setup(bone));
+        world.push(world_transform(bone.transform_mode, &local, parent.as_ref()));
+    }
+    world
+}
+
+pub(crate) fn region_offsets(region: &RegionAttachment, trim: Option<&AtlasRegion>) -> [(f32, f32); 4] {
+    let mut local_x = -region.width / 2.0 * region.scale_x;
+    let mut local_y = -region.height / 2.0 * region.scale_y;
+    let mut local_x2 = local_x + region.width * region.scale_x;
+    let mut local_y2 = local_y + region.height * region.scale_y;
+    if let Some(trim) = trim.filter(|t| t.orig_width > 0 && t.orig_height > 0) {
+        let region_scale_x = region.width / trim.orig_width as f32 * region.scale_x;
+        let region_scale_y = region.height / trim.orig_height as f32 * region.scale_y;
+        local_x += trim.offset_x as f32 * region_scale_x;
+        local_y += trim.offset_y as f32 * region_scale_y;
+        local_x2 = local_x + trim.width as f32 * region_scale_x;
+        local_y2 = local_y + trim.height as f32 * region_scale_y;
+    }
+    let cos = cos_deg(region.rotation);
+    let sin = sin_deg(region.rotation);
+    let corner = |x: f32, y: f32| (x * cos - y * sin + region.x, x * sin + y * cos + region.y);
+    [
+        corner(local_x, local_y),
+        corner(local_x, local_y2),
+        corner(local_x2, local_y2),
+        corner(local_x2, local_y),
+    ]
+}
+
+pub(crate) fn mesh_world_vertices(
+    vertices: &Vertices,
+    deform: Option<&[f32]>,
+    slot_bone: &WorldTransform,
+    world: &[WorldTransform],
+) -> Vec<(f32, f32)> {
+    if !vertices.is_weighted() {
+        return deform
+            .filter(|d| d.len() == vertices.values.len())
+            .unwrap_or(&vertices.values)
+            .chunks_exact(2)
+            .map(|v| slot_bone.apply(v[0], v[1]))
+            .collect();
+    }
+    let offset = |i: usize| deform.and_then(|d| d.get(i)).copied().unwrap_or(0.0);
+    let mut result = Vec::with_capacity(vertices.count);
+    let mut b = 0;
+    let mut w = 0;
+    let mut f = 0;
+    while b < vertices.bones.len() {
+        let bone_count = vertices.bones[b];
+        b += 1;
+        let (mut wx, mut wy) = (0.0, 0.0);
+        for _ in 0..bone_count {
+            let bone = vertices
+                .bones
+                .get(b)
+                .and_then(|i| world.get(*i))
+                .unwrap_or(&WorldTransform::IDENTITY);
+            let (Some(&vx), Some(&vy), Some(&weight)) =
+                (vertices.values.get(w), vertices.values.get(w + 1), vertices.values.get(w + 2))
+            else {
+                break;
+            };
+            let (x, y) = bone.apply(vx + offset(f), vy + offset(f + 1));
+            wx += x * weight;
+            wy += y * weight;
+            b += 1;
+            w += 3;
+            f += 2;
+        }
+        result.push((wx, wy));
+    }
+    result
+}
+
+pub(crate) fn setup_bounds(skeleton: &SkeletonData) -> Option<Bounds> {
+    let locals: Vec<LocalTransform> = skeleton.bones.iter().map(LocalTransform::setup).collect();
+    let world = world_transforms(skeleton, &locals);
+    let skin = skeleton.default_skin();
+    let mut points = Vec::new();
+    for (slot_index, slot) in skeleton.slots.iter().enumerate() {
+        let Some(name) = &slot.attachment else {
+            continue;
+        };
+        let Some(bone) = world.get(slot.bone) else {
+            continue;
+        };
+        match skeleton.attachment(skin, slot_index, name) {
+            Some(Attachment::Region(region)) => {
+                points.extend(region_offsets(region, None).iter().map(|(x, y)| bone.apply(*x, *y)));
+            }
+            Some(Attachment::Mesh(mesh)) => {
+                points.extend(mesh_world_vertices(&mesh.vertices, None, bone, &world));
+            }
+            _ => {}
+        }
+    }
+    points_bounds(&points)
+}
+
+pub(crate) fn points_bounds(points: &[(f32, f32)]) -> Option<Bounds> {
+    let points: Vec<(f32, f32)> = points
+        .iter()
+        .copied()
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .collect();
+    let first = points.first()?;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (first.0, first.1, first.0, first.1);
+    for (x, y) in &points {
+        min_x = min_x.min(*x);
+        min_y = min_y.min(*y);
+        max_x = max_x.max(*x);
+        max_y = max_y.max(*y);
+    }
+    Some(Bounds {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    })
+}
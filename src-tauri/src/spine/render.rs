@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+
+use super::animation::Pose;
+use super::data::*;
+use super::pose::{self, Bounds, WorldTransform};
+use crate::atlas::{Atlas, AtlasRegion};
+
+const MAX_RENDER_SIZE: u32 = 8192;
+const MAX_FPS: f32 = 120.0;
+const MAX_FRAMES: usize = 1800;
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RenderOptions {
+    pub(crate) skin: Option<String>,
+    pub(crate) animation: Option<String>,
+    #[serde(default)]
+    pub(crate) time: f32,
+    pub(crate) scale: Option<f32>,
+    pub(crate) max_size: Option<u32>,
+    #[serde(default)]
+    pub(crate) padding: u32,
+}
+
+struct Page {
+    image: RgbaImage,
+    scale_x: f32,
+    scale_y: f32,
+}
+
+struct Draw {
+    page: usize,
+    positions: Vec<(f32, f32)>,
+    uvs: Vec<(f32, f32)>,
+    triangles: Vec<usize>,
+    color: Color,
+    blend: BlendMode,
+    clip: Option<usize>,
+}
+
+#[derive(Default)]
+struct Frame {
+    draws: Vec<Draw>,
+    clips: Vec<Vec<(f32, f32)>>,
+}
+
+pub(crate) struct SpineRenderer {
+    skeleton: SkeletonData,
+    pages: Vec<Page>,
+    regions: HashMap<String, (usize, AtlasRegion)>,
+}
+
+fn premultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let a = pixel[3] as u32;
+        for c in 0..3 {
+            pixel[c] = ((pixel[c] as u32 * a + 127) / 255) as u8;
+        }
+    }
+}
+
+fn region_uvs(region: &AtlasRegion) -> [(f32, f32); 4] {
+    let (u, v) = (region.x as f32, region.y as f32);
+    let (width, height) = (region.width as f32, region.height as f32);
+    match region.degrees {
+        90 => {
+            let (u2, v2) = (u + height, v + width);
+            [(u2, v2), (u, v2), (u, v), (u2, v)]
+        }
+        180 => {
+            let (u2, v2) = (u + width, v + height);
+            [(u2, v), (u2, v2), (u, v2), (u, v)]
+        }
+        270 => {
+            let (u2, v2) = (u + height, v + width);
+            [(u, v), (u2, v), (u2, v2), (u, v2)]
+        }
+        _ => {
+            let (u2, v2) = (u + width, v + height);
+            [(u, v2), (u, v), (u2, v), (u2, v2)]
+        }
+    }
+}
+
+fn mesh_uvs(region: &AtlasRegion, region_uvs: &[f32]) -> Vec<(f32, f32)> {
+    let (x, y) = (region.x as f32, region.y as f32);
+    let (width, height) = (region.width as f32, region.height as f32);
+    let (orig_width, orig_height) = (region.orig_width as f32, region.orig_height as f32);
+    let (offset_x, offset_y) = (region.offset_x as f32, region.offset_y as f32);
+    let uv = region_uvs.chunks_exact(2).map(|uv| (uv[0], uv[1]));
+    match region.degrees {
+        90 => {
+            let u = x - (orig_height - offset_y - height);
+            let v = y - (orig_width - offset_x - width);
+            uv.map(|(ru, rv)| (u + rv * orig_height, v + (1.0 - ru) * orig_width)).collect()
+        }
+        180 => {
+            let u = x - (orig_width - offset_x - width);
+            let v = y - offset_y;
+            uv.map(|(ru, rv)| (u + (1.0 - ru) * orig_width, v + (1.0 - rv) * orig_height)).collect()
+        }
+        270 => {
+            let u = x - offset_y;
+            let v = y - offset_x;
+            uv.map(|(ru, rv)| (u + (1.0 - rv) * orig_height, v + ru * orig_width)).collect()
+        }
+        _ => {
+            let u = x - offset_x;
+            let v = y - (orig_height - offset_y - height);
+            uv.map(|(ru, rv)| (u + ru * orig_width, v + rv * orig_height)).collect()
+        }
+    }
+}
+
+fn sample(page: &Page, u: f32, v: f32) -> [f32; 4] {
+    let x = (u * page.scale_x - 0.5).clamp(0.0, (page.image.width() - 1) as f32);
+    let y = (v * page.scale_y - 0.5).clamp(0.0, (page.image.height() - 1) as f32);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let x1 = (x0 + 1).min(page.image.width() - 1);
+    let y1 = (y0 + 1).min(page.image.height() - 1);
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let p00 = page.image.get_pixel(x0, y0);
+    let p10 = page.image.get_pixel(x1, y0);
+    let p01 = page.image.get_pixel(x0, y1);
+    let p11 = page.image.get_pixel(x1, y1);
+    let mut out = [0.0; 4];
+    for (c, value) in out.iter_mut().enumerate() {
+        let top = p00[c] as f32 * (1.0 - fx) + p10[c] as f32 * fx;
+        let bottom = p01[c] as f32 * (1.0 - fx) + p11[c] as f32 * fx;
+        *value = (top * (1.0 - fy) + bottom * fy) / 255.0;
+    }
+    out
+}
+
+fn blend(dst: &mut [f32; 4], src: [f32; 4], mode: BlendMode) {
+    let inv_alpha = 1.0 - src[3];
+    for c in 0..3 {
+        dst[c] = match mode {
+            BlendMode::Normal => src[c] + dst[c] * inv_alpha,
+            BlendMode::Additive => src[c] + dst[c],
+            BlendMode::Multiply => src[c] * dst[c] + dst[c] * inv_alpha,
+            BlendMode::Screen => src[c] + dst[c] * (1.0 - src[c]),
+        }
+        .min(1.0);
+    }
+    dst[3] = (src[3] + dst[3] * inv_alpha).min(1.0);
+}
+
+fn edge(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+fn covers(weight: f32, a: (f32, f32), b: (f32, f32)) -> bool {
+    weight > 0.0 || (weight == 0.0 && (b.1 > a.1 || (b.1 == a.1 && b.0 > a.0)))
+}
+
+fn polygon_mask(polygon: &[(f32, f32)], width: u32, height: u32) -> Vec<bool> {
+    let mut mask = vec![false; (width * height) as usize];
+    for y in 0..height {
+        let py = y as f32 + 0.5;
+        let mut crossings: Vec<f32> = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .filter(|(a, b)| (a.1 <= py) != (b.1 <= py))
+            .map(|(a, b)| a.0 + (py - a.1) / (b.1 - a.1) * (b.0 - a.0))
+            .collect();
+        crossings.sort_by(|a, b| a.total_cmp(b));
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0] - 0.5).ceil().max(0.0) as u32;
+            let end = ((span[1] - 0.5).ceil().max(0.0) as u32).min(width);
+            for x in start..end {
+                mask[(y * width + x) as usize] = true;
+            }
+        }
+    }
+    mask
+}
+
+impl SpineRenderer {
+    pub(crate) fn load(skeleton_path: &Path, atlas_path: &Path) -> Result<Self, String> {
+        let atlas_dir = atlas_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Self::load_with(skeleton_path, atlas_path, |page| atlas_dir.join(page))
+    }
+
+    pub(crate) fn load_with(
+        skeleton_path: &Path,
+        atlas_path: &Path,
+        page_path: impl Fn(&str) -> PathBuf,
+    ) -> Result<Self, String> {
+        let skeleton = super::read_skeleton(skeleton_path)?;
+        if skeleton.constraints > 0 {
+            return Err(format!(
+                "{} uses IK, transform, path or physics constraints, which are not supported",
+                skeleton_path.display()
+            ));
+        }
+        let atlas = Atlas::read(atlas_path)?;
+        let mut pages = Vec::with_capacity(atlas.pages.len());
+        let mut regions = HashMap::new();
+        for (index, page) in atlas.pages.into_iter().enumerate() {
+            let path = page_path(&page.name);
+            let mut image = image::open(&path)
+                .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?
+                .to_rgba8();
+            if !page.pma {
+                premultiply(&mut image);
+            }
+            let (width, height) = image.dimensions();
+            let scale_x = if page.width > 0 { width as f32 / page.width as f32 } else { 1.0 };
+            let scale_y = if page.height > 0 { height as f32 / page.height as f32 } else { 1.0 };
+            for region in page.regions {
+                regions.entry(region.name.clone()).or_insert((index, region));
+            }
+            pages.push(Page {
+                image,
+                scale_x,
+                scale_y,
+            });
+        }
+        Ok(Self {
+            skeleton,
+            pages,
+            regions,
+        })
+    }
+
+    pub(crate) fn pose(&self, animation: Option<&str>, time: f32) -> Result<Pose, String> {
+        let mut pose = Pose::setup(&self.skeleton);
+        if let Some(name) = animation {
+            let animation = self
+                .skeleton
+                .animations
+                .iter()
+                .find(|a| a.name == name)
+                .ok_or_else(|| format!("Animation not found: {}", name))?;
+            pose.apply(&self.skeleton, animation, time);
+        }
+        Ok(pose)
+    }
+
+    fn skin(&self, name: Option<&str>) -> Result<Option<usize>, String> {
+        match name {
+            Some(name) => self
+                .skeleton
+                .find_skin(name)
+                .map(Some)
+                .ok_or_else(|| format!("Skin not found: {}", name)),
+            None => Ok(self.skeleton.default_skin()),
+        }
+    }
+
+    fn frame(&self, pose: &Pose, skin: Option<usize>) -> Frame {
+        let world = pose::world_transforms(&self.skeleton, &pose.locals);
+        let mut frame = Frame::default();
+        let mut clipping: Option<(usize, Option<usize>)> = None;
+        for &slot_index in &pose.draw_order {
+            let Some(slot) = self.skeleton.slots.get(slot_index) else {
+                continue;
+            };
+            let bone = world.get(slot.bone).unwrap_or(&WorldTransform::IDENTITY);
+            let name = pose.attachments.get(slot_index).cloned().flatten();
+            let attachment = name
+                .as_deref()
+                .and_then(|name| self.skeleton.attachment(skin, slot_index, name));
+            let deform = name
+                .and_then(|name| pose.deforms.get(&(slot_index, name)))
+                .map(Vec::as_slice);
+            let color = pose.colors.get(slot_index).copied().unwrap_or_default();
+            let clip = clipping.map(|(clip, _)| clip);
+            match attachment {
+                Some(Attachment::Clipping(attachment)) if clipping.is_none() => {
+                    frame
+                        .clips
+                        .push(pose::mesh_world_vertices(&attachment.vertices, deform, bone, &world));
+                    clipping = Some((frame.clips.len() - 1, attachment.end_slot));
+                    continue;
+                }
+                Some(Attachment::Region(region)) => {
+                    if let Some((page, atlas_region)) = self.regions.get(&region.path) {
+                        frame.draws.push(Draw {
+                            page: *page,
+                            positions: pose::region_offsets(region, Some(atlas_region))
+                                .iter()
+                                .map(|(x, y)| bone.apply(*x, *y))
+                                .collect(),
+                            uvs: region_uvs(atlas_region).to_vec(),
+                            triangles: vec![0, 1, 2, 2, 3, 0],
+                            color: color.mul(region.color),
+                            blend: slot.blend,
+                            clip,
+                        });
+                    }
+                }
+                Some(Attachment::Mesh(mesh)) => {
+                    if let Some((page, atlas_region)) = self.regions.get(&mesh.path) {
+                        frame.draws.push(Draw {
+                            page: *page,
+                            positions: pose::mesh_world_vertices(&mesh.vertices, deform, bone, &world),
+                            uvs: mesh_uvs(atlas_region, &mesh.uvs),
+                            triangles: mesh.triangles.clone(),
+                            color: color.mul(mesh.color),
+                            blend: slot.blend,
+                            clip,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            if clipping.is_some_and(|(_, end)| end == Some(slot_index)) {
+                clipping = None;
+            }
+        }
+        frame
+    }
+
+    fn bounds(frame: &Frame) -> Option<Bounds> {
+        let points: Vec<(f32, f32)> = frame
+            .draws
+            .iter()
+            .filter(|draw| draw.color.a > 0.0)
+            .flat_map(|draw| draw.positions.iter().copied())
+            .collect();
+        pose::points_bounds(&points)
+    }
+
+    fn rasterize(&self, frame: &Frame, bounds: Bounds, options: &RenderOptions) -> RgbaImage {
+        let max_size = options.max_size.unwrap_or(MAX_RENDER_SIZE).clamp(1, MAX_RENDER_SIZE);
+        let padding = options.padding.min(max_size / 4);
+        let fit = (max_size - padding * 2) as f32 / bounds.width.max(bounds.height).max(1.0);
+        let scale = options.scale.filter(|s| *s > 0.0).unwrap_or(1.0).min(fit);
+        let width = (bounds.width * scale).ceil().max(1.0) as u32 + padding * 2;
+        let height = (bounds.height * scale).ceil().max(1.0) as u32 + padding * 2;
+        let to_pixel = |(x, y): (f32, f32)| {
+            (
+                (x - bounds.x) * scale + padding as f32,
+                (bounds.y + bounds.height - y) * scale + padding as f32,
+            )
+        };
+        let masks: Vec<Vec<bool>> = frame
+            .clips
+            .iter()
+            .map(|polygon| {
+                let polygon: Vec<(f32, f32)> = polygon.iter().copied().map(to_pixel).collect();
+                polygon_mask(&polygon, width, height)
+            })
+            .collect();
+        let mut canvas = vec![[0.0f32; 4]; (width * height) as usize];
+        for draw in &frame.draws {
+            let Some(page) = self.pages.get(draw.page) else {
+                continue;
+            };
+            let mask = draw.clip.and_then(|clip| masks.get(clip));
+            let c = draw.color;
+            let tint = [c.r * c.a, c.g * c.a, c.b * c.a, c.a];
+            for triangle in draw.triangles.chunks_exact(3) {
+                let (Some(p0), Some(p1), Some(p2)) = (
+                    draw.positions.get(triangle[0]),
+                    draw.positions.get(triangle[1]),
+                    draw.positions.get(triangle[2]),
+                ) else {
+                    continue;
+                };
+                let (Some(t0), Some(t1), Some(t2)) = (
+                    draw.uvs.get(triangle[0]),
+                    draw.uvs.get(triangle[1]),
+                    draw.uvs.get(triangle[2]),
+                ) else {
+                    continue;
+                };
+                let (p0, mut p1, mut p2) = (to_pixel(*p0), to_pixel(*p1), to_pixel(*p2));
+                let (mut t1, mut t2) = (*t1, *t2);
+                let mut area = edge(p0, p1, p2);
+                if !area.is_finite() || area.abs() < 1e-6 {
+                    continue;
+                }
+                if area < 0.0 {
+                    std::mem::swap(&mut p1, &mut p2);
+                    std::mem::swap(&mut t1, &mut t2);
+                    area = -area;
+                }
+                let min_x = p0.0.min(p1.0).min(p2.0).floor().max(0.0) as u32;
+                let min_y = p0.1.min(p1.1).min(p2.1).floor().max(0.0) as u32;
+                let max_x = (p0.0.max(p1.0).max(p2.0).ceil().max(0.0) as u32).min(width);
+                let max_y = (p0.1.max(p1.1).max(p2.1).ceil().max(0.0) as u32).min(height);
+                for y in min_y..max_y {
+                    for x in min_x..max_x {
+                        let p = (x as f32 + 0.5, y as f32 + 0.5);
+                        let w0 = edge(p1, p2, p);
+                        let w1 = edge(p2, p0, p);
+                        let w2 = edge(p0, p1, p);
+                        if !covers(w0, p1, p2) || !covers(w1, p2, p0) || !covers(w2, p0, p1) {
+                            continue;
+                        }
+                        let index = (y * width + x) as usize;
+                        if mask.is_some_and(|mask| !mask[index]) {
+                            continue;
+                        }
+                        let (l0, l1, l2) = (w0 / area, w1 / area, w2 / area);
+                        let u = t0.0 * l0 + t1.0 * l1 + t2.0 * l2;
+                        let v = t0.1 * l0 + t1.1 * l1 + t2.1 * l2;
+                        let texel = sample(page, u, v);
+                        let src = [
+                            texel[0] * tint[0],
+                            texel[1] * tint[1],
+                            texel[2] * tint[2],
+                            texel[3] * tint[3],
+                        ];
+                        blend(&mut canvas[index], src, draw.blend);
+                    }
+                }
+            }
+        }
+        let mut image = RgbaImage::new(width, height);
+        for (pixel, value) in image.pixels_mut().zip(&canvas) {
+            let a = value[3];
+            if a <= 0.0 {
+                continue;
+            }
+            let channel = |v: f32| ((v / a).clamp(0.0, 1.0) * 255.0).round() as u8;
+            *pixel = image::Rgba([channel(value[0]), channel(value[1]), channel(value[2]), (a * 255.0).round() as u8]);
+        }
+        image
+    }
+
+    pub(crate) fn render(&self, options: &RenderOptions) -> Result<RgbaImage, String> {
+        let skin = self.skin(options.skin.as_deref())?;
+        let pose = self.pose(options.animation.as_deref(), options.time)?;
+        let frame = self.frame(&pose, skin);
+        let bounds = Self::bounds(&frame).ok_or("Skeleton has nothing to render")?;
+        Ok(self.rasterize(&frame, bounds, options))
+    }
+
+    pub(crate) fn render_animation(
+        &self,
+        options: &RenderOptions,
+        fps: f32,
+        mut on_frame: impl FnMut(usize, RgbaImage) -> Result<(), String>,
+    ) -> Result<usize, String> {
+        let name = options.animation.as_deref().ok_or("No animation selected")?;
+        let duration = self
+            .skeleton
+            .animations
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.duration)
+            .ok_or_else(|| format!("Animation not found: {}", name))?;
+        let skin = self.skin(options.skin.as_deref())?;
+        let fps = if fps > 0.0 { fps.min(MAX_FPS) } else { 30.0 };
+        let count = ((duration * fps).floor() as usize + 1).min(MAX_FRAMES);
+        let mut points = Vec::new();
+        for i in 0..count {
+            let pose = self.pose(Some(name), i as f32 / fps)?;
+            if let Some(b) = Self::bounds(&self.frame(&pose, skin)) {
+                points.extend([(b.x, b.y), (b.x + b.width, b.y + b.height)]);
+            }
+        }
+        let bounds = pose::points_bounds(&points).ok_or("Animation has nothing to render")?;
+        for i in 0..count {
+            let pose = self.pose(Some(name), i as f32 / fps)?;
+            on_frame(i, self.rasterize(&self.frame(&pose, skin), bounds, options))?;
+        }
+        Ok(count)
+    }
+}
+
+pub(crate) fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
+
+pub(crate) fn export_frames(
+    skeleton_path: &Path,
+    atlas_path: &Path,
+    options: &RenderOptions,
+    fps: Option<f32>,
+    output_dir: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    let renderer = SpineRenderer::load(skeleton_path, atlas_path)?;
+    fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+    let stem = file_stem(options.animation.as_deref().unwrap_or("setup"));
+    let mut paths = Vec::new();
+    let mut write_frame = |i: usize, frame: RgbaImage| {
+        let path = output_dir.join(format!("{}_{:04}.png", stem, i));
+        frame
+            .save(&path)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        paths.push(path);
+        Ok(())
+    };
+    match fps {
+        Some(fps) if options.animation.is_some() => {
+            renderer.render_animation(options, fps, &mut write_frame)?;
+        }
+        _ => write_frame(0, renderer.render(options)?)?,
+    }
+    Ok(paths)
+}
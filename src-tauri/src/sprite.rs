@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+use serde_json::Value;
+
+use crate::psd::{self, PsdLayer};
+use crate::spine::render::file_stem;
+
+pub(crate) struct LayeredSprite {
+    dir: PathBuf,
+    meta: Value,
+    textures: HashMap<String, RgbaImage>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+struct Canvas {
+    min_x: f32,
+    min_y: f32,
+    width: u32,
+    height: u32,
+}
+
+impl Canvas {
+    fn fit<'a>(body: &RgbaImage, rects: impl Iterator<Item = &'a Rect>) -> Self {
+        let (mut min_x, mut min_y) = (0.0f32, 0.0f32);
+        let (mut max_x, mut max_y) = (body.width() as f32, body.height() as f32);
+        for rect in rects {
+            min_x = min_x.min(rect.x);
+            min_y = min_y.min(rect.y);
+            max_x = max_x.max(rect.x + rect.w);
+            max_y = max_y.max(rect.y + rect.h);
+        }
+        Self {
+            min_x,
+            min_y,
+            width: (max_x - min_x).ceil().max(1.0) as u32,
+            height: (max_y - min_y).ceil().max(1.0) as u32,
+        }
+    }
+
+    fn x(&self, x: f32) -> i64 {
+        (x - self.min_x).round() as i64
+    }
+
+    fn y(&self, y: f32) -> i64 {
+        (y - self.min_y).round() as i64
+    }
+}
+
+fn number(value: &Value, pointer: &str) -> Option<f32> {
+    value.pointer(pointer).and_then(|v| v.as_f64()).map(|v| v as f32)
+}
+
+fn non_zero(value: Option<f32>) -> Option<f32> {
+    value.filter(|v| *v != 0.0)
+}
+
+fn is_meta_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_lowercase())
+        .is_some_and(|n| n == "meta.json" || n.ends_with(".meta.json"))
+}
+
+fn character_name(meta_path: &Path) -> String {
+    let file_name = meta_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let lower = file_name.to_lowercase();
+    if lower == "meta.json" {
+        return meta_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .unwrap_or("meta")
+            .to_string();
+    }
+    file_name[..file_name.len() - ".meta.json".len()].to_string()
+}
+
+fn collect_meta_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    if path.is_file() {
+        if is_meta_file(path) {
+            files.push(path.to_path_buf());
+        }
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort_by(|a, b| crate::compare_natural(&a.to_string_lossy(), &b.to_string_lossy()));
+    for entry in entries {
+        if entry.is_dir() && crate::skip_dir(&entry) {
+            continue;
+        }
+        collect_meta_files(&entry, files)?;
+    }
+    Ok(())
+}
+
+fn crop_flipped(texture: &RgbaImage, rect: Rect) -> RgbaImage {
+    let x = rect.x.max(0.0) as u32;
+    let top = (texture.height() as f32 - (rect.y + rect.h)).max(0.0) as u32;
+    let w = (rect.w as u32).min(texture.width().saturating_sub(x));
+    let h = (rect.h as u32).min(texture.height().saturating_sub(top));
+    image::imageops::crop_imm(texture, x, top, w, h).to_image()
+}
+
+impl LayeredSprite {
+    pub(crate) fn open(dir: &Path, meta_file: &str) -> Result<Self, String> {
+        let data = fs::read(dir.join(meta_file)).map_err(|e| format!("Failed to read {}: {}", meta_file, e))?;
+        let meta: Value = serde_json::from_slice(&data).map_err(|e| format!("Failed to parse {}: {}", meta_file, e))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            meta,
+            textures: HashMap::new(),
+        })
+    }
+
+    pub(crate) fn faces(&self) -> Vec<String> {
+        self.meta
+            .get("faces")
+            .and_then(|v| v.as_object())
+            .map(|faces| faces.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn default_face(&self) -> Option<String> {
+        let faces = self.faces();
+        if faces.iter().any(|f| f == "normal") {
+            return Some("normal".to_string());
+        }
+        faces.into_iter().next()
+    }
+
+    fn default_texture(&self) -> Option<&str> {
+        self.meta.get("atlas").and_then(|v| v.as_str()).filter(|s| !s.is_empty())
+    }
+
+    fn body_texture(&self) -> Option<&str> {
+        self.meta
+            .pointer("/bodySpriteRect/texture")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .or_else(|| self.default_texture())
+    }
+
+    fn face_texture(&self, face: &str) -> Option<&str> {
+        self.meta
+            .get("faces")
+            .and_then(|faces| faces.get(face))
+            .and_then(|f| f.get("texture"))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .or_else(|| self.default_texture())
+    }
+
+    pub(crate) fn texture_files(&self, face: Option<&str>) -> Vec<String> {
+        let mut files: Vec<String> = self.body_texture().map(|t| t.to_string()).into_iter().collect();
+        if let Some(texture) = face.and_then(|f| self.face_texture(f))
+            && !files.iter().any(|f| f == texture)
+        {
+            files.push(texture.to_string());
+        }
+        files
+    }
+
+    fn texture(&mut self, name: &str) -> Result<&RgbaImage, String> {
+        if !self.textures.contains_key(name) {
+            let image = image::open(self.dir.join(name))
+                .map_err(|e| format!("Failed to load texture {}: {}", name, e))?
+                .to_rgba8();
+            self.textures.insert(name.to_string(), image);
+        }
+        Ok(&self.textures[name])
+    }
+
+    fn body_layer(&mut self) -> Result<(RgbaImage, Rect), String> {
+        let texture_name = self.body_texture().ok_or("Layered sprite has no body texture")?.to_string();
+        let rect = self.meta.get("bodySpriteRect").map(|_| Rect {
+            x: number(&self.meta, "/bodySpriteRect/x").unwrap_or(0.0),
+            y: number(&self.meta, "/bodySpriteRect/y").unwrap_or(0.0),
+            w: number(&self.meta, "/bodySpriteRect/w").unwrap_or(0.0),
+            h: number(&self.meta, "/bodySpriteRect/h").unwrap_or(0.0),
+        });
+        let texture = self.texture(&texture_name)?;
+        let rect = rect.unwrap_or(Rect {
+            x: 0.0,
+            y: 0.0,
+            w: texture.width() as f32,
+            h: texture.height() as f32,
+        });
+        Ok((crop_flipped(texture, rect), rect))
+    }
+
+    fn face_layer(&mut self, face: &str, body: Rect) -> Result<Option<(RgbaImage, Rect)>, String> {
+        let Some(face_meta) = self.meta.get("faces").and_then(|f| f.get(face)).cloned() else {
+            return Ok(None);
+        };
+        let Some(texture_name) = self.face_texture(face).map(|t| t.to_string()) else {
+            return Ok(None);
+        };
+        let meta = &self.meta;
+        let rect = Rect {
+            x: number(&face_meta, "/textureRect/x").unwrap_or(0.0),
+            y: number(&face_meta, "/textureRect/y").unwrap_or(0.0),
+            w: number(&face_meta, "/textureRect/w").unwrap_or(0.0),
+            h: number(&face_meta, "/textureRect/h").unwrap_or(0.0),
+        };
+        let fw = non_zero(number(&face_meta, "/w")).unwrap_or(rect.w);
+        let fh = non_zero(number(&face_meta, "/h")).unwrap_or(rect.h);
+        let bow = non_zero(number(meta, "/bodySpriteRect/ow")).unwrap_or(body.w);
+        let boh = non_zero(number(meta, "/bodySpriteRect/oh")).unwrap_or(body.h);
+        let scale_x = bow / non_zero(number(meta, "/bodyRect/sizeDelta/x")).unwrap_or(bow);
+        let scale_y = boh / non_zero(number(meta, "/bodyRect/sizeDelta/y")).unwrap_or(boh);
+        let center_x = number(meta, "/faceContentRect/anchoredPosition/x").unwrap_or(0.0) * scale_x;
+        let center_y = -number(meta, "/faceContentRect/anchoredPosition/y").unwrap_or(0.0) * scale_y;
+        let offset_x = number(&face_meta, "/textureRectOffset/x").unwrap_or(0.0);
+        let offset_y = number(&face_meta, "/textureRectOffset/y").unwrap_or(0.0);
+        let body_offset_x = number(meta, "/bodySpriteRect/textureRectOffset/x").unwrap_or(0.0);
+        let body_offset_y = number(meta, "/bodySpriteRect/textureRectOffset/y").unwrap_or(0.0);
+        let w = rect.w * scale_x;
+        let h = rect.h * scale_y;
+        let crop_center_x = -bow * 0.5 + body_offset_x + body.w * 0.5;
+        let crop_center_y = boh * 0.5 - (body_offset_y + body.h) + body.h * 0.5;
+        let x = center_x - (fw * 0.5 - offset_x) * scale_x - crop_center_x + body.w * 0.5;
+        let y = center_y + (fh * 0.5 - offset_y) * scale_y - h - crop_center_y + body.h * 0.5;
+        let mut image = crop_flipped(self.texture(&texture_name)?, rect);
+        let target_w = w.round().max(1.0) as u32;
+        let target_h = h.round().max(1.0) as u32;
+        if (target_w, target_h) != image.dimensions() {
+            image = image::imageops::resize(&image, target_w, target_h, image::imageops::FilterType::Triangle);
+        }
+        Ok(Some((image, Rect { x, y, w, h })))
+    }
+
+    pub(crate) fn compose(&mut self, face: Option<&str>) -> Result<RgbaImage, String> {
+        let (body, body_rect) = self.body_layer()?;
+        let face_layer = match face {
+            Some(face) => self.face_layer(face, body_rect)?,
+            None => None,
+        };
+        let canvas = Canvas::fit(&body, face_layer.iter().map(|(_, rect)| rect));
+        let mut image = RgbaImage::new(canvas.width, canvas.height);
+        image::imageops::overlay(&mut image, &body, canvas.x(0.0), canvas.y(0.0));
+        if let Some((face, rect)) = &face_layer {
+            image::imageops::overlay(&mut image, face, canvas.x(rect.x), canvas.y(rect.y));
+        }
+        Ok(image)
+    }
+
+    pub(crate) fn export_psd(&mut self, path: &Path) -> Result<(), String> {
+        let (body, body_rect) = self.body_layer()?;
+        let mut faces = Vec::new();
+        for face in self.faces() {
+            if let Some((image, rect)) = self.face_layer(&face, body_rect)? {
+                faces.push((face, image, rect));
+            }
+        }
+        let canvas = Canvas::fit(&body, faces.iter().map(|(_, _, rect)| rect));
+        let mut composite = RgbaImage::new(canvas.width, canvas.height);
+        image::imageops::overlay(&mut composite, &body, canvas.x(0.0), canvas.y(0.0));
+        let mut layers = vec![PsdLayer {
+            name: "body".to_string(),
+            image: body,
+            left: canvas.x(0.0) as i32,
+            top: canvas.y(0.0) as i32,
+            visible: true,
+        }];
+        layers.extend(faces.into_iter().map(|(name, image, rect)| PsdLayer {
+            name,
+            image,
+            left: canvas.x(rect.x) as i32,
+            top: canvas.y(rect.y) as i32,
+            visible: false,
+        }));
+        psd::write(path, &composite, &layers)
+    }
+
+    pub(crate) fn export_expressions(&mut self, output_dir: &Path) -> Result<Vec<PathBuf>, String> {
+        fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+        let faces = self.faces();
+        let variants: Vec<Option<String>> = if faces.is_empty() {
+            vec![None]
+        } else {
+            faces.into_iter().map(Some).collect()
+        };
+        let mut paths = Vec::with_capacity(variants.len());
+        for face in variants {
+            let image = self.compose(face.as_deref())?;
+            let path = output_dir.join(format!("{}.png", file_stem(face.as_deref().unwrap_or("body"))));
+            image
+                .save(&path)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+pub(crate) fn export_all_expressions(sources: &[PathBuf], output_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut meta_files = Vec::new();
+    for source in sources {
+        collect_meta_files(source, &mut meta_files)?;
+    }
+    if meta_files.is_empty() {
+        return Err("No layered sprites found".to_string());
+    }
+    let mut paths = Vec::new();
+    for meta_path in meta_files {
+        let meta_dir = meta_path.parent().unwrap_or(Path::new("."));
+        let meta_name = meta_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let target = output_dir.join(file_stem(&character_name(&meta_path)));
+        let result = LayeredSprite::open(meta_dir, meta_name).and_then(|mut sprite| sprite.export_expressions(&target));
+        match result {
+            Ok(written) => paths.extend(written),
+            Err(e) => eprintln!("Failed to export {}: {}", meta_path.display(), e),
+        }
+    }
+    Ok(paths)
+}
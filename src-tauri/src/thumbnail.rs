@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use image::RgbaImage;
+
+use crate::atlas::Atlas;
+use crate::overlay::StableHasher;
+use crate::scene::{SceneData, SceneKind, is_image};
+use crate::spine::render::{RenderOptions, SpineRenderer};
+use crate::sprite::LayeredSprite;
+
+const THUMBNAIL_VERSION: u32 = 3;
+pub(crate) const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+
+pub(crate) fn thumbnail_root() -> PathBuf {
+    std::env::temp_dir().join("spive2d").join("thumbnails")
+}
+
+fn cache_key(sources: &[PathBuf], size: u32) -> Result<String, String> {
+    let mut hasher = StableHasher::new();
+    hasher.write(&THUMBNAIL_VERSION.to_le_bytes());
+    hasher.write(&size.to_le_bytes());
+    for source in sources {
+        let metadata = fs::metadata(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let path = fs::canonicalize(source).unwrap_or_else(|_| source.clone());
+        hasher.write(path.to_string_lossy().as_bytes());
+        hasher.write(&[0]);
+        hasher.write(&metadata.len().to_le_bytes());
+        hasher.write(&modified.as_nanos().to_le_bytes());
+    }
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+pub(crate) fn trim(image: &RgbaImage) -> RgbaImage {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    if min_x > max_x {
+        return image.clone();
+    }
+    image::imageops::crop_imm(image, min_x, min_y, max_x - min_x + 1, max_y - min_y + 1).to_image()
+}
+
+fn fit(image: RgbaImage, size: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    if width <= size && height <= size {
+        return image;
+    }
+    let scale = size as f32 / width.max(height) as f32;
+    let target_w = ((width as f32 * scale).round() as u32).max(1);
+    let target_h = ((height as f32 * scale).round() as u32).max(1);
+    image::imageops::resize(&image, target_w, target_h, image::imageops::FilterType::Triangle)
+}
+
+fn first_texture(dir: &Path, scene: &SceneData) -> Option<PathBuf> {
+    let pages = scene.atlas_pages.iter().map(|page| scene.scene_path(&page.name));
+    let textures = scene.assets.iter().filter(|asset| is_image(asset)).cloned();
+    pages
+        .chain(textures)
+        .map(|rel_path| scene.resolve(dir, &rel_path))
+        .find(|path| path.is_file())
+}
+
+fn spine_files(dir: &Path, scene: &SceneData) -> Option<(PathBuf, PathBuf, Vec<PathBuf>)> {
+    if scene.kind != SceneKind::Spine || scene.is_merged || scene.atlas_ext.is_empty() {
+        return None;
+    }
+    let skeleton = scene.resolve(dir, &format!("{}{}", scene.name, scene.main_ext));
+    let atlas_base = scene.atlas_bases.get(&scene.name).unwrap_or(&scene.name);
+    let atlas = scene.resolve(dir, &format!("{}{}", atlas_base, scene.atlas_ext));
+    if !skeleton.is_file() || !atlas.is_file() {
+        return None;
+    }
+    let pages: Vec<PathBuf> = Atlas::read(&atlas)
+        .ok()?
+        .pages
+        .iter()
+        .map(|page| spine_page(dir, scene, &atlas, &page.name))
+        .collect();
+    pages.iter().all(|page| page.is_file()).then_some((skeleton, atlas, pages))
+}
+
+fn spine_page(dir: &Path, scene: &SceneData, atlas: &Path, page: &str) -> PathBuf {
+    let path = scene.resolve(dir, &scene.scene_path(page));
+    if path.is_file() {
+        return path;
+    }
+    atlas.parent().unwrap_or(dir).join(page)
+}
+
+fn render_spine(
+    dir: &Path,
+    scene: &SceneData,
+    skeleton: &Path,
+    atlas: &Path,
+    size: u32,
+) -> Result<RgbaImage, String> {
+    let renderer = SpineRenderer::load_with(skeleton, atlas, |page| spine_page(dir, scene, atlas, page))?;
+    renderer.render(&RenderOptions {
+        max_size: Some(size),
+        ..Default::default()
+    })
+}
+
+fn render_texture(path: &Path) -> Result<RgbaImage, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?
+        .to_rgba8();
+    Ok(trim(&image))
+}
+
+fn write_thumbnail(path: &Path, image: RgbaImage, size: u32) -> Result<(), String> {
+    fs::create_dir_all(thumbnail_root()).map_err(|e| format!("Failed to create thumbnail directory: {}", e))?;
+    fit(image, size)
+        .save(path)
+        .map_err(|e| format!("Failed to write thumbnail: {}", e))
+}
+
+pub(crate) fn scene_thumbnail(dir: &Path, scene: &SceneData, size: u32) -> Result<PathBuf, String> {
+    if let Some((skeleton, atlas, pages)) = spine_files(dir, scene) {
+        let mut sources = vec![skeleton.clone(), atlas.clone()];
+        sources.extend(pages);
+        let path = thumbnail_root().join(format!("{}.png", cache_key(&sources, size)?));
+        if path.is_file() {
+            return Ok(path);
+        }
+        match render_spine(dir, scene, &skeleton, &atlas, size) {
+            Ok(image) => {
+                write_thumbnail(&path, trim(&image), size)?;
+                return Ok(path);
+            }
+            Err(e) => eprintln!("Failed to render {}: {}", scene.name, e),
+        }
+    }
+    let (sources, sprite) = match scene.kind {
+        SceneKind::LayeredSprite => {
+            let meta_file = format!("{}{}", scene.name, scene.main_ext);
+            let meta_path = scene.resolve(dir, &meta_file);
+            let meta_dir = meta_path.parent().unwrap_or(dir);
+            let meta_name = meta_path.file_name().and_then(|f| f.to_str()).unwrap_or(&meta_file);
+            let sprite = LayeredSprite::open(meta_dir, meta_name)?;
+            let face = sprite.default_face();
+            let mut sources = vec![meta_path.clone()];
+            sources.extend(
+                sprite
+                    .texture_files(face.as_deref())
+                    .iter()
+                    .map(|texture| meta_dir.join(texture)),
+            );
+            (sources, Some((sprite, face)))
+        }
+        _ => {
+            let texture = first_texture(dir, scene).ok_or_else(|| format!("No texture found for {}", scene.name))?;
+            (vec![texture], None)
+        }
+    };
+    let path = thumbnail_root().join(format!("{}.png", cache_key(&sources, size)?));
+    if path.is_file() {
+        return Ok(path);
+    }
+    let image = match sprite {
+        Some((mut sprite, face)) => trim(&sprite.compose(face.as_deref())?),
+        None => render_texture(&sources[0])?,
+    };
+    write_thumbnail(&path, image, size)?;
+    Ok(path)
+}
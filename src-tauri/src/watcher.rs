@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use notify::{RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::scene::{ScanOptions, SceneData};
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+const MAX_DEBOUNCE: Duration = Duration::from_secs(5);
+
+pub(crate) struct RootWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+#[derive(serde::Serialize, Clone)]
+pub(crate) struct DirChanges {
+    dir: String,
+    scenes: Vec<SceneData>,
+    added: Vec<SceneData>,
+    removed: Vec<String>,
+    modified: Vec<SceneData>,
+}
+
+#[derive(serde::Serialize, Clone)]
+pub(crate) struct SceneFilesModified {
+    dir: String,
+    scene: String,
+    files: Vec<String>,
+}
+
+struct WatchedRoot {
+    root: PathBuf,
+    canonical_root: PathBuf,
+    merge_sequential: bool,
+    options: ScanOptions,
+    scenes: HashMap<String, Vec<SceneData>>,
+}
+
+pub(crate) fn start(
+    app_handle: &AppHandle,
+    root: &Path,
+    merge_sequential: bool,
+    options: ScanOptions,
+    scenes: HashMap<String, Vec<SceneData>>,
+) {
+    let watched = WatchedRoot {
+        root: root.to_path_buf(),
+        canonical_root: fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf()),
+        merge_sequential,
+        options,
+        scenes,
+    };
+    let watcher = match RootWatcher::new(watched, app_handle.clone()) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            eprintln!("Failed to watch {}: {}", root.display(), e);
+            None
+        }
+    };
+    *app_handle.state::<crate::AppState>().watcher.lock().unwrap() = watcher;
+}
+
+pub(crate) fn stop(app_handle: &AppHandle) {
+    app_handle.state::<crate::AppState>().watcher.lock().unwrap().take();
+}
+
+impl RootWatcher {
+    fn new(mut watched: WatchedRoot, app_handle: AppHandle) -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else { return };
+            if matches!(event.kind, notify::EventKind::Access(_)) {
+                return;
+            }
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        watcher
+            .watch(&watched.root, RecursiveMode::Recursive)
+            .map_err(|e| e.to_string())?;
+        std::thread::spawn(move || {
+            while let Ok(first) = rx.recv() {
+                let mut changed = BTreeSet::from([first]);
+                let deadline = Instant::now() + MAX_DEBOUNCE;
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        break;
+                    }
+                    match rx.recv_timeout(DEBOUNCE.min(remaining)) {
+                        Ok(path) => {
+                            changed.insert(path);
+                        }
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                watched.rescan(&changed, &app_handle);
+            }
+        });
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+fn touches(scene: &SceneData, rel_path: &str) -> bool {
+    if scene.assets.iter().any(|asset| asset == rel_path) {
+        return true;
+    }
+    let rel_lower = rel_path.to_lowercase();
+    let prefix = scene.name_prefix();
+    let mut bases = vec![scene.name.clone()];
+    if scene.is_merged {
+        bases.extend(scene.files.iter().cloned());
+    }
+    bases.extend(scene.atlas_bases.values().cloned());
+    bases.extend(scene.atlas_pages.iter().map(|page| format!("{}{}", prefix, page.name)));
+    bases
+        .iter()
+        .any(|base| rel_lower.starts_with(&base.to_lowercase()))
+}
+
+impl WatchedRoot {
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.canonical_root))
+            .ok()
+    }
+
+    fn affected_dirs(&self, changed: &BTreeSet<PathBuf>) -> BTreeMap<PathBuf, Vec<(PathBuf, String)>> {
+        let mut dirs: BTreeMap<PathBuf, Vec<(PathBuf, String)>> = BTreeMap::new();
+        for path in changed {
+            let Some(rel) = self.relative(path) else { continue };
+            let mut components = rel.components();
+            let Some(first) = components.next() else { continue };
+            let rest = components.as_path();
+            let sub_dir = self.root.join(first);
+            if rest.as_os_str().is_empty() {
+                dirs.entry(self.root.clone()).or_default().push((
+                    path.clone(),
+                    first.as_os_str().to_string_lossy().into_owned(),
+                ));
+                if sub_dir.is_dir() || self.scenes.contains_key(&crate::dir_key(&sub_dir)) {
+                    dirs.entry(sub_dir).or_default();
+                }
+            } else {
+                let rel_path = rest
+                    .to_string_lossy()
+                    .replace(std::path::MAIN_SEPARATOR, "/");
+                dirs.entry(sub_dir).or_default().push((path.clone(), rel_path));
+            }
+        }
+        dirs
+    }
+
+    fn scan(&self, dir: &Path) -> Vec<SceneData> {
+        let result = if *dir == self.root {
+            crate::process_files(dir, &self.root, self.merge_sequential, &self.options)
+        } else if dir.is_dir() && !crate::skip_dir(dir) {
+            crate::process_directory(dir, &self.root, self.merge_sequential, &self.options)
+        } else {
+            Ok(Vec::new())
+        };
+        result.unwrap_or_else(|e| {
+            eprintln!("Failed to rescan {}: {}", dir.display(), e);
+            Vec::new()
+        })
+    }
+
+    fn rescan(&mut self, changed: &BTreeSet<PathBuf>, app_handle: &AppHandle) {
+        let mut changes = Vec::new();
+        let mut modified_files = Vec::new();
+        for (dir, files) in self.affected_dirs(changed) {
+            let key = crate::dir_key(&dir);
+            let scenes = self.scan(&dir);
+            let old_scenes = self.scenes.remove(&key).unwrap_or_default();
+            let mut added = Vec::new();
+            let mut modified = Vec::new();
+            for scene in &scenes {
+                let Some(old) = old_scenes.iter().find(|old| old.name == scene.name) else {
+                    added.push(scene.clone());
+                    continue;
+                };
+                let touched: Vec<String> = files
+                    .iter()
+                    .filter(|(_, rel_path)| touches(old, rel_path) || touches(scene, rel_path))
+                    .map(|(path, _)| path.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))
+                    .collect();
+                let changed_data = serde_json::to_value(old).ok() != serde_json::to_value(scene).ok();
+                if touched.is_empty() && !changed_data {
+                    continue;
+                }
+                modified.push(scene.clone());
+                modified_files.push(SceneFilesModified {
+                    dir: key.clone(),
+                    scene: scene.name.clone(),
+                    files: touched,
+                });
+            }
+            let removed: Vec<String> = old_scenes
+                .iter()
+                .filter(|old| !scenes.iter().any(|scene| scene.name == old.name))
+                .map(|old| old.name.clone())
+                .collect();
+            if !scenes.is_empty() {
+                self.scenes.insert(key.clone(), scenes.clone());
+            }
+            if added.is_empty() && removed.is_empty() && modified.is_empty() {
+                continue;
+            }
+            changes.push(DirChanges {
+                dir: key,
+                scenes,
+                added,
+                removed,
+                modified,
+            });
+        }
+        if !changes.is_empty() {
+            let _ = app_handle.emit("scenes-changed", changes);
+        }
+        if !modified_files.is_empty() {
+            let _ = app_handle.emit("scene-files-modified", modified_files);
+        }
+    }
+}
@@ -1,5 +1,12 @@
 import { loadSetting } from './settings.js';
 
+function detectMaxTextureSize() {
+  if (typeof document === 'undefined') return 0;
+  const canvas = document.createElement('canvas');
+  const gl = canvas.getContext('webgl2') || canvas.getContext('webgl');
+  return gl ? gl.getParameter(gl.MAX_TEXTURE_SIZE) : 0;
+}
+
 let initialized = $state(false);
 let processing = $state(false);
 let viewport = $state({
@@ -39,8 +46,14 @@ let alphaMode = $state(loadSetting('spive2d_alpha_mode', 'pma'));
 let textureFilter = $state(loadSetting('spive2d_texture_filter', 'linear'));
 let skipUnity = $state(loadSetting('spive2d_skip_unity', 'false') === 'true');
 let mergeSequential = $state(loadSetting('spive2d_merge_sequential', 'false') === 'true');
+let mergeLimit = $state(Number(loadSetting('spive2d_merge_limit', '20')));
+let includeSpineMetadata = $state(loadSetting('spive2d_include_spine_metadata', 'false') === 'true');
+let fixInPlace = $state(loadSetting('spive2d_fix_in_place', 'false') === 'true');
+let companionsBehind = $state(loadSetting('spive2d_companions_behind', '_bg'));
+let companionsFront = $state(loadSetting('spive2d_companions_front', '_fg'));
 let enableIdleAndBreathing = $state(loadSetting('spive2d_enable_idle_and_breathing', 'false') === 'true');
 let enableMouseTracking = $state(loadSetting('spive2d_enable_mouse_tracking', 'false') === 'true');
+const maxTextureSize = detectMaxTextureSize();
 const SCALE_MAX = 16;
 const SCALE_MIN = 0.5;
 export const appState = {
@@ -74,10 +87,31 @@ export const appState = {
   set textureFilter(v) { textureFilter = v; },
   get mergeSequential() { return mergeSequential; },
   set mergeSequential(v) { mergeSequential = v; },
+  get mergeLimit() { return mergeLimit; },
+  set mergeLimit(v) { mergeLimit = v; },
+  get includeSpineMetadata() { return includeSpineMetadata; },
+  set includeSpineMetadata(v) { includeSpineMetadata = v; },
+  get fixInPlace() { return fixInPlace; },
+  set fixInPlace(v) { fixInPlace = v; },
+  get companionsBehind() { return companionsBehind; },
+  set companionsBehind(v) { companionsBehind = v; },
+  get companionsFront() { return companionsFront; },
+  set companionsFront(v) { companionsFront = v; },
+  get scanOptions() {
+    const rules = (value, role) => value.split(',').map(s => s.trim()).filter(Boolean).map(suffix => ({ suffix, role }));
+    return {
+      includeMetadata: includeSpineMetadata,
+      fixInPlace,
+      companions: [...rules(companionsBehind, 'behind'), ...rules(companionsFront, 'front')],
+      mergeLimit: Math.max(0, Math.floor(Number(mergeLimit) || 0)),
+      maxTextureSize: maxTextureSize || null,
+    };
+  },
   get enableIdleAndBreathing() { return enableIdleAndBreathing; },
   set enableIdleAndBreathing(v) { enableIdleAndBreathing = v; },
   get enableMouseTracking() { return enableMouseTracking; },
   set enableMouseTracking(v) { enableMouseTracking = v; },
+  get maxTextureSize() { return maxTextureSize; },
   get skipUnity() { return skipUnity; },
   set skipUnity(v) { skipUnity = v; },
   SCALE_MAX,
@@ -1,4 +1,4 @@
-import { convertFileSrc } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
 import { open as tauriOpen } from '@tauri-apps/plugin-dialog';
 import { openPath } from '@tauri-apps/plugin-opener';
 import { downloadDir, join, dirname } from '@tauri-apps/api/path';
@@ -48,3 +48,39 @@ export async function openImageFile() {
 export function getAssetUrl(filePath) {
   return convertFileSrc(filePath);
 }
+
+export async function resolveLive2DModelSource(dirName, scene) {
+  let ext = '.model3.json';
+  if (scene.mainExt.includes('.moc3')) ext = '.model3.json';
+  else if (scene.mainExt.includes('.moc')) ext = '.json';
+  const rawUrl = `${dirName}${scene.name}${ext}`;
+  const isRemote = rawUrl.startsWith('http://') || rawUrl.startsWith('https://');
+  let url = isRemote ? rawUrl : convertFileSrc(rawUrl);
+  url += (url.includes('?') ? '&' : '?') + 't=' + Date.now();
+  const overlay = isRemote ? {} : scene.overlay ?? {};
+  const overlayPath = overlay[`${scene.name}${ext}`];
+  const prefix = scene.name.slice(0, scene.name.lastIndexOf('/') + 1);
+  const hasTextureOverlay = Object.keys(overlay).some(key => /\.(png|jpe?g|webp)$/i.test(key));
+  if (!overlayPath && !hasTextureOverlay) return url;
+  const response = await fetch(overlayPath ? convertFileSrc(overlayPath) : url);
+  const model = await response.json();
+  const textures = model.FileReferences?.Textures ?? model.textures;
+  if (Array.isArray(textures)) {
+    textures.forEach((file, i) => {
+      const texturePath = overlay[`${prefix}${file}`];
+      if (texturePath) textures[i] = convertFileSrc(texturePath);
+    });
+  }
+  return { ...model, url };
+}
+
+export async function prepareScene(dirName, scene) {
+  if (!scene.looseImages || dirName.startsWith('http://') || dirName.startsWith('https://')) return scene;
+  return invoke('prepare_scene', { dir: dirName, scene });
+}
+
+export async function getSceneThumbnailUrl(dirName, scene, size) {
+  if (dirName.startsWith('http://') || dirName.startsWith('https://')) return null;
+  const path = await invoke('get_scene_thumbnail', { dir: dirName, scene, size });
+  return convertFileSrc(path);
+}
@@ -1,6 +1,6 @@
 import { BaseRenderer } from './BaseRenderer.js';
 import { createSorter } from '../utils.js';
-import { convertFileSrc } from '@tauri-apps/api/core';
+import { resolveLive2DModelSource } from '../fileManager.js';
 import { showNotification } from '../notificationStore.svelte.js';
 import { appState } from '../appState.svelte.js';
 
@@ -89,19 +89,12 @@ export class Live2DRenderer extends BaseRenderer {
     if (!this.#isExport && this.#canvas) {
       this.#canvas.style.display = 'block';
     }
-    let ext = '.model3.json';
-    if (scene.mainExt.includes('.moc3')) ext = '.model3.json';
-    else if (scene.mainExt.includes('.moc')) ext = '.json';
-    const rawUrl = `${dirName}${scene.name}${ext}`;
-    let url = (rawUrl.startsWith('http://') || rawUrl.startsWith('https://'))
-      ? rawUrl
-      : convertFileSrc(rawUrl);
-    url += (url.includes('?') ? '&' : '?') + 't=' + Date.now();
     const { live2d: { Live2DModel } } = PIXI;
     try {
+      const source = await resolveLive2DModelSource(dirName, scene);
       const scaleMode = appState.textureFilter === 'nearest' ? PIXI.SCALE_MODES.NEAREST : PIXI.SCALE_MODES.LINEAR;
       PIXI.settings.SCALE_MODE = scaleMode;
-      const model = await Live2DModel.from(url, {
+      const model = await Live2DModel.from(source, {
         autoInteract: false,
         ...(appState.enableIdleAndBreathing ? {} : { idleMotionGroup: 'None' })
       });
@@ -191,13 +191,12 @@ export class SpineRendererBase extends BaseRenderer {
     this._assetManager = new this._spine.AssetManager(this._ctx.gl, '');
     setupSpineAssetManager(this._assetManager, this._spine, this._ctx.gl);
     const mainExt = scene.mainExt;
-    const atlasExt = scene.atlasExt;
     const normalizedDirName = dirName.endsWith('/') ? dirName : `${dirName}/`;
     const makePath = (name, ext) => `${normalizedDirName}${name}${ext}`;
     const loadModel = (name) => {
       if (!this._isFileJson) this._assetManager.loadBinary(makePath(name, mainExt));
       else this._assetManager.loadText(makePath(name, mainExt));
-      this._assetManager.loadTextureAtlas(makePath(name, atlasExt));
+      this._assetManager.loadTextureAtlas(this._atlasPath(scene, normalizedDirName, name));
     };
     if (scene.isMerged) {
       for (const name of scene.files) loadModel(name);
@@ -707,11 +706,16 @@ export class SpineRendererBase extends BaseRenderer {
     this._syncAllHiddenAttachments();
   }
 
+  _atlasPath(scene, dirName, name) {
+    const atlasFile = `${scene.atlasBases?.[name] ?? name}${scene.atlasExt}`;
+    return scene.overlay?.[atlasFile] ?? `${dirName}${atlasFile}`;
+  }
+
   async _loadSkeleton(fileName) {
     const sceneInfo = this._fileNames;
     const normalizedDirName = this._dirName.endsWith('/') ? this._dirName : `${this._dirName}/`;
     const makePath = (name, ext) => `${normalizedDirName}${name}${ext}`;
-    const atlasPath = makePath(fileName, sceneInfo.atlasExt);
+    const atlasPath = this._atlasPath(sceneInfo, normalizedDirName, fileName);
     const atlas = this._assetManager.get(atlasPath);
     if (!atlas) return null;
     if (atlas.regions) setupAtlas(atlas);
@@ -929,11 +933,12 @@ export class SpineRendererBase extends BaseRenderer {
 
   _getSortedSkeletonKeys() {
     const sceneInfo = this._fileNames;
+    const roles = new Map((sceneInfo.companions || []).map(c => [c.file, c.role]));
     return Object.keys(this._skeletons).sort((a, b) => {
       const getLayer = (k) => {
         if (!sceneInfo.isMerged && k === '0') return 0;
-        const name = (sceneInfo.isMerged ? sceneInfo.files[parseInt(k)] : sceneInfo.files[parseInt(k) - 1] || '').toLowerCase();
-        return name.includes('_fg') ? 1 : (name.includes('_bg') ? -1 : 0);
+        const role = roles.get(sceneInfo.isMerged ? sceneInfo.files[parseInt(k)] : sceneInfo.files[parseInt(k) - 1]);
+        return role === 'front' ? 1 : (role === 'behind' ? -1 : 0);
       };
       const la = getLayer(a), lb = getLayer(b);
       return la !== lb ? la - lb : parseInt(a) - parseInt(b);
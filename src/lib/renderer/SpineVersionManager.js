@@ -43,6 +43,13 @@ export class SpineVersionManager {
   }
 
   static async detectVersion(dirName, scene) {
+    const mainExtLower = scene.mainExt.toLowerCase();
+    if (scene.kind === 'spine' && scene.version && !mainExtLower.endsWith('.asset')) {
+      const version = scene.version.substring(0, 3);
+      if (SPINE_VERSIONS.includes(version)) {
+        return { version, isJson: mainExtLower.endsWith('.json') };
+      }
+    }
     let baseName = scene.name;
     if (scene.isMerged && scene.files.length > 0) {
       baseName = scene.files[0];
@@ -3,6 +3,9 @@ import { SpineRenderer } from './SpineRenderer.js';
 import { LayeredSpriteRenderer } from './LayeredSpriteRenderer.js';
 
 export function createRenderer(scene, isExport = false) {
+  if (scene.kind === 'layeredSprite') return new LayeredSpriteRenderer(isExport);
+  if (scene.kind === 'cubism3' || scene.kind === 'cubism2') return new Live2DRenderer(isExport);
+  if (scene.kind === 'spine') return new SpineRenderer(isExport);
   const ext = scene.mainExt;
   if ((scene.name === 'meta' && (ext === '.json' || ext.includes('meta.json'))) || ext.includes('meta.json')) return new LayeredSpriteRenderer(isExport);
   if (ext.includes('.moc') || ext.includes('.model3.json') || ext.includes('.model.json')) return new Live2DRenderer(isExport);